@@ -1,8 +1,10 @@
 use anchor_lang::{prelude::*, solana_program::clock::Clock};
-use anchor_lang::solana_program::keccak::hash;
+use anchor_lang::solana_program::keccak::hashv;
 use anchor_lang::solana_program::{system_instruction, program::invoke};
 use anchor_lang::solana_program::rent::Rent;
+use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
 use anchor_lang::AccountDeserialize;
+use anchor_lang::Discriminator;
 
 pub const FEE_RECEIVER_PUBKEY: Pubkey = Pubkey::new_from_array([
     2, 236, 12, 165, 146, 38, 247, 217,
@@ -14,12 +16,96 @@ pub const FEE_RECEIVER_PUBKEY: Pubkey = Pubkey::new_from_array([
 
 declare_id!("FFL71XjBkjq5gce7EtpB7Wa5p8qnRNueLKSzM4tkEMoc");
 
+/// Key allowed to change the network-wide settings in `NetworkConfig`.
+pub const NETWORK_AUTHORITY: Pubkey = FEE_RECEIVER_PUBKEY;
+
+/// Commitments are `keccak(vote || password)`; kept for questions created before
+/// commitments were bound to the voter.
+pub const COMMITMENT_VERSION_LEGACY: u8 = 0;
+/// Commitments are `keccak(COMMITMENT_DOMAIN || voter || question || vote || password)`.
+pub const COMMITMENT_VERSION_BOUND: u8 = 1;
+pub const COMMITMENT_DOMAIN: &[u8] = b"truth-net:commit:v1";
+
+/// Discriminator + provider_deposit + unbonding_period +
+/// default_min_membership_age. Configs created before the unbonding period was
+/// added are 8 + 8 bytes until migrated.
+pub const NETWORK_CONFIG_SPACE: usize = 8 + 8 + 8 + 8;
+
+/// Space every question was allocated before categorical questions.
+pub const LEGACY_QUESTION_SPACE: usize = 450;
+
+/// Space allocated for a `UserRecord`. Records created before provider stake
+/// was added are 8 + 80 bytes until migrated.
+pub const USER_RECORD_SPACE: usize = 8 + 144;
+
+/// Space allocated for a `VoterRecord`. Records created before the
+/// delegated revealer was added are 8 + 200 bytes until migrated.
+pub const VOTER_RECORD_SPACE: usize = 8 + 256;
+
+/// Most options a categorical question can have.
+pub const MAX_OPTIONS: usize = 8;
+pub const MAX_OPTION_LABEL_LEN: usize = 32;
+
+/// Longest resolution-criteria or source URI a question can carry.
+pub const MAX_URI_LEN: usize = 200;
+
+/// Longest long-form content a question can attach.
+pub const MAX_CONTENT_LEN: u32 = 4096;
+
+/// The option with the most weight wins; a tie for first resolves to 0.
+pub const WINNING_RULE_PLURALITY: u8 = 0;
+/// The winner also needs more than half of the revealed weight, otherwise the
+/// question resolves to 0 like a tie.
+pub const WINNING_RULE_MAJORITY: u8 = 1;
+
+/// Reserved vote for an ambiguous or unresolvable question. When it wins,
+/// `winning_option` is set to this value (0 stays a tie, 255 unset).
+pub const OPTION_INVALID: u8 = 254;
+/// Share of the reward, after the fee, paid to revealers of an invalid
+/// question; the rest is refunded to the asker.
+pub const INVALID_REWARD_BPS: u64 = 5_000;
+/// Outcome when fewer voters or less weight than the asker's quorum was
+/// revealed. Nobody is paid or slashed and the reward goes back to the asker.
+pub const OUTCOME_NO_QUORUM: u8 = 253;
+/// Outcome when the winner falls short of the asker's required majority.
+/// Settled like OUTCOME_NO_QUORUM.
+pub const OUTCOME_CONTESTED: u8 = 252;
+/// Outcome of a conditional question whose parent resolved the other way.
+/// The reward goes back to the asker.
+pub const OUTCOME_VOID: u8 = 251;
+
+pub const QUESTION_TYPE_OPTIONS: u8 = 0;
+/// Providers reveal a number; the question resolves to the weighted median.
+pub const QUESTION_TYPE_SCALAR: u8 = 1;
+/// Most reveals a scalar question can hold.
+pub const MAX_SCALAR_REVEALS: usize = 64;
+
+/// Fixed-point scale of `UserRecord.reward_per_delegated`.
+pub const DELEGATION_REWARD_PRECISION: u128 = 1_000_000_000_000;
+
+/// Vote weight is the provider's reputation.
+pub const VOTE_WEIGHT_REPUTATION: u8 = 0;
+/// Vote weight is reputation × (1 + log2(1 + staked SOL)), counting own and
+/// delegated stake.
+pub const VOTE_WEIGHT_REPUTATION_LOG_STAKE: u8 = 1;
+
 
 /// An empty account for the vault.
 /// This account will only hold lamports and no other data.
 #[account]
 pub struct Vault {}
 
+/// Per-provider stake vault.
+/// Holds the deposit paid in `join_network` and any stake added later until
+/// the provider leaves.
+#[account]
+pub struct StakeVault {}
+
+/// Per-operator delegation pool.
+/// Holds stake delegated to the operator and delegators' unclaimed rewards.
+#[account]
+pub struct DelegationPool {}
+
 
 #[program]
 pub mod truth_network {
@@ -66,7 +152,25 @@ pub mod truth_network {
             );
         }
     
+        // Escrow the provider deposit in the user's stake vault
+        let deposit = ctx.accounts.network_config.provider_deposit;
+        if deposit > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    &user.key(),
+                    &ctx.accounts.stake_vault.key(),
+                    deposit,
+                ),
+                &[
+                    user.to_account_info(),
+                    ctx.accounts.stake_vault.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+
         user_record.user = *user.key;
+        user_record.stake = deposit;
         user_record.reputation = 0;
         user_record.total_earnings = 0;
         user_record.total_revealed_votes = 0;
@@ -80,8 +184,218 @@ pub mod truth_network {
     
       
     
-    pub fn leave_network(_ctx: Context<LeaveNetwork>) -> Result<()> {
-        msg!("Vault and UserRecord closed successfully. Goodbye, {}!", _ctx.accounts.user.key());
+    pub fn add_stake(ctx: Context<AddStake>, amount: u64) -> Result<()> {
+        let user_record = &mut ctx.accounts.user_record;
+
+        require!(amount > 0, VotingError::InsufficientFunds);
+        require!(user_record.leave_requested_at == 0, VotingError::LeaveRequested);
+
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.user.key(),
+                &ctx.accounts.stake_vault.key(),
+                amount,
+            ),
+            &[
+                ctx.accounts.user.to_account_info(),
+                ctx.accounts.stake_vault.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        user_record.stake = user_record
+            .stake
+            .checked_add(amount)
+            .ok_or(VotingError::Overflow)?;
+
+        msg!("Added {} lamports of stake. Total stake: {}", amount, user_record.stake);
+        Ok(())
+    }
+
+    pub fn request_leave(ctx: Context<RequestLeave>) -> Result<()> {
+        let user_record = &mut ctx.accounts.user_record;
+
+        require!(user_record.leave_requested_at == 0, VotingError::LeaveAlreadyRequested);
+
+        user_record.leave_requested_at = Clock::get()?.unix_timestamp;
+
+        msg!(
+            "{} requested to leave. Unbonding for {} seconds",
+            ctx.accounts.user.key(),
+            ctx.accounts.network_config.unbonding_period
+        );
+        Ok(())
+    }
+
+    pub fn complete_leave(ctx: Context<CompleteLeave>) -> Result<()> {
+        let user_record = &ctx.accounts.user_record;
+
+        require!(user_record.leave_requested_at != 0, VotingError::LeaveNotRequested);
+        require!(user_record.open_commitments == 0, VotingError::OpenCommitmentsExist);
+        require!(user_record.delegated_stake == 0, VotingError::DelegationsOutstanding);
+        require!(
+            Clock::get()?.unix_timestamp
+                >= user_record
+                    .leave_requested_at
+                    .saturating_add(ctx.accounts.network_config.unbonding_period),
+            VotingError::UnbondingNotOver
+        );
+
+        msg!(
+            "Stake of {} lamports returned to {}",
+            ctx.accounts.user_record.stake,
+            ctx.accounts.user.key()
+        );
+        msg!("Vault and UserRecord closed successfully. Goodbye, {}!", ctx.accounts.user.key());
+        Ok(())
+    }
+
+    pub fn set_commission(ctx: Context<SetCommission>, commission_bps: u16) -> Result<()> {
+        require!(commission_bps <= 10_000, VotingError::InvalidCommission);
+
+        let user_record = &mut ctx.accounts.user_record;
+        let now = Clock::get()?.unix_timestamp;
+
+        // An increase that is already due counts as the current commission
+        user_record.commission_bps = commission_at(user_record, now);
+        user_record.pending_commission_bps = 0;
+        user_record.pending_commission_at = 0;
+
+        if commission_bps <= user_record.commission_bps {
+            user_record.commission_bps = commission_bps;
+            msg!("Commission of {} set to {} bps", ctx.accounts.user.key(), commission_bps);
+        } else {
+            // Increases wait out the unbonding period so delegators can leave first
+            user_record.pending_commission_bps = commission_bps;
+            user_record.pending_commission_at =
+                now.saturating_add(ctx.accounts.network_config.unbonding_period);
+            msg!(
+                "Commission of {} rises to {} bps at {}",
+                ctx.accounts.user.key(),
+                commission_bps,
+                user_record.pending_commission_at
+            );
+        }
+        Ok(())
+    }
+
+    pub fn delegate_stake(ctx: Context<DelegateStake>, amount: u64) -> Result<()> {
+        let operator_record = &mut ctx.accounts.operator_record;
+        let delegation = &mut ctx.accounts.delegation;
+
+        require!(amount > 0, VotingError::InsufficientFunds);
+        require!(operator_record.leave_requested_at == 0, VotingError::LeaveRequested);
+
+        invoke(
+            &system_instruction::transfer(
+                &ctx.accounts.delegator.key(),
+                &ctx.accounts.delegation_pool.key(),
+                amount,
+            ),
+            &[
+                ctx.accounts.delegator.to_account_info(),
+                ctx.accounts.delegation_pool.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ],
+        )?;
+
+        settle_delegation_rewards(delegation, operator_record);
+
+        delegation.delegator = ctx.accounts.delegator.key();
+        delegation.operator = ctx.accounts.operator.key();
+        delegation.amount = delegation
+            .amount
+            .checked_add(amount)
+            .ok_or(VotingError::Overflow)?;
+        delegation.reward_debt = accrued_delegation_rewards(delegation.amount, operator_record);
+
+        operator_record.delegated_stake = operator_record
+            .delegated_stake
+            .checked_add(amount)
+            .ok_or(VotingError::Overflow)?;
+
+        msg!(
+            "{} delegated {} lamports to {}",
+            delegation.delegator,
+            amount,
+            delegation.operator
+        );
+        Ok(())
+    }
+
+    pub fn claim_delegation_rewards(ctx: Context<ClaimDelegationRewards>) -> Result<()> {
+        let delegation = &mut ctx.accounts.delegation;
+
+        settle_delegation_rewards(delegation, &ctx.accounts.operator_record);
+        delegation.reward_debt = accrued_delegation_rewards(delegation.amount, &ctx.accounts.operator_record);
+
+        let rewards = delegation.pending_rewards;
+        require!(rewards > 0, VotingError::InsufficientFunds);
+        delegation.pending_rewards = 0;
+
+        transfer_from_vault(
+            &ctx.accounts.delegation_pool.to_account_info(),
+            &ctx.accounts.delegator.to_account_info(),
+            rewards,
+        )?;
+
+        msg!("Delegation rewards claimed: {} lamports", rewards);
+        Ok(())
+    }
+
+    pub fn request_undelegate(ctx: Context<RequestUndelegate>, amount: u64) -> Result<()> {
+        let operator_record = &mut ctx.accounts.operator_record;
+        let delegation = &mut ctx.accounts.delegation;
+
+        require!(
+            amount > 0 && amount <= delegation.amount,
+            VotingError::InsufficientFunds
+        );
+
+        start_undelegation(delegation, operator_record, amount)
+    }
+
+    /// Lets an operator who is leaving undelegate a delegator's whole stake, so
+    /// a delegation they cannot control does not keep them in the network.
+    pub fn force_undelegate(ctx: Context<ForceUndelegate>) -> Result<()> {
+        let operator_record = &mut ctx.accounts.operator_record;
+        let delegation = &mut ctx.accounts.delegation;
+
+        require!(operator_record.leave_requested_at != 0, VotingError::LeaveNotRequested);
+        require!(delegation.amount > 0, VotingError::InsufficientFunds);
+
+        let amount = delegation.amount;
+        start_undelegation(delegation, operator_record, amount)
+    }
+
+    pub fn complete_undelegate(ctx: Context<CompleteUndelegate>) -> Result<()> {
+        let delegation = &mut ctx.accounts.delegation;
+
+        require!(delegation.undelegating_amount > 0, VotingError::InsufficientFunds);
+        require!(
+            Clock::get()?.unix_timestamp
+                >= delegation
+                    .undelegate_requested_at
+                    .saturating_add(ctx.accounts.network_config.unbonding_period),
+            VotingError::UnbondingNotOver
+        );
+
+        // Rewards settled so far are paid out together with the stake
+        let payout = delegation.undelegating_amount + delegation.pending_rewards;
+        delegation.undelegating_amount = 0;
+        delegation.pending_rewards = 0;
+
+        transfer_from_vault(
+            &ctx.accounts.delegation_pool.to_account_info(),
+            &ctx.accounts.delegator.to_account_info(),
+            payout,
+        )?;
+
+        msg!("Undelegation completed. Returned {} lamports", payout);
+
+        if delegation.amount == 0 {
+            delegation.close(ctx.accounts.delegator.to_account_info())?;
+        }
         Ok(())
     }
            
@@ -89,87 +403,192 @@ pub mod truth_network {
     pub fn create_question(
         ctx: Context<CreateQuestion>,
         question_text: String,
+        option_labels: Option<Vec<String>>,
         reward: u64,
         commit_end_time: i64,
         reveal_end_time: i64,
+        settings: QuestionSettings,
     ) -> Result<()> {
-        let question_counter = &mut ctx.accounts.question_counter;
-        let question_key = ctx.accounts.question.key();
+        let options = match option_labels {
+            Some(labels) => {
+                require!(labels.len() == 2, VotingError::InvalidOptionCount);
+                labels
+            }
+            None => vec!["True".to_string(), "False".to_string()],
+        };
+        init_question(ctx, question_text, options, None, reward, commit_end_time, reveal_end_time, settings)
+    }
+
+    pub fn create_categorical_question(
+        ctx: Context<CreateQuestion>,
+        question_text: String,
+        options: Vec<String>,
+        reward: u64,
+        commit_end_time: i64,
+        reveal_end_time: i64,
+        settings: QuestionSettings,
+    ) -> Result<()> {
+        init_question(ctx, question_text, options, None, reward, commit_end_time, reveal_end_time, settings)
+    }
 
-        // Minimum length check for question text
+    pub fn create_scalar_question(
+        ctx: Context<CreateQuestion>,
+        question_text: String,
+        range: ScalarRange,
+        reward: u64,
+        commit_end_time: i64,
+        reveal_end_time: i64,
+        settings: QuestionSettings,
+    ) -> Result<()> {
+        require!(range.min_value < range.max_value, VotingError::InvalidScalarRange);
+        require!(range.decimals <= 18, VotingError::InvalidScalarRange);
         require!(
-            question_text.len() >= 10,
-            VotingError::QuestionTooShort
+            (range.tolerance as i128) <= range.max_value as i128 - range.min_value as i128,
+            VotingError::InvalidScalarRange
         );
 
-        // Maximum length check for question text
+        init_question(ctx, question_text, Vec::new(), Some(range), reward, commit_end_time, reveal_end_time, settings)
+    }
+    
+                        
+
+    pub fn init_question_content(ctx: Context<InitQuestionContent>, total_len: u32) -> Result<()> {
+        let question = &mut ctx.accounts.question;
+        let content = &mut ctx.accounts.question_content;
+
         require!(
-            question_text.len() <= 150,
-            VotingError::QuestionTooLong
+            Clock::get()?.unix_timestamp < question.commit_end_time,
+            VotingError::CommitPhaseEnded
         );
-        
-        // Ensure commit and reveal times are valid.
-        let now = Clock::get()?.unix_timestamp;
-        require!(now < commit_end_time, VotingError::VotingEnded);
-        require!(commit_end_time < reveal_end_time, VotingError::InvalidTimeframe);
-        
-        // Require reward to be at least 0.05 SOL (in lamports)
-        const MIN_REWARD_LAMPORTS: u64 = 50_000_000; // 0.05 SOL
+        // Providers who already committed never saw the content
+        require!(question.committed_voters == 0, VotingError::QuestionHasCommitments);
+        require!(total_len > 0 && total_len <= MAX_CONTENT_LEN, VotingError::ContentTooLong);
+
+        content.question = question.key();
+        content.locked = false;
+        content.content = vec![0u8; total_len as usize];
+        question.has_content = true;
+
+        msg!("Question content of {} bytes created", total_len);
+
+        Ok(())
+    }
+
+    pub fn write_question_content(
+        ctx: Context<WriteQuestionContent>,
+        offset: u32,
+        chunk: Vec<u8>,
+    ) -> Result<()> {
+        let content = &mut ctx.accounts.question_content;
+
+        require!(!content.locked, VotingError::ContentLocked);
+
+        let start = offset as usize;
+        let end = start + chunk.len();
+        require!(end <= content.content.len(), VotingError::ContentTooLong);
+
+        content.content[start..end].copy_from_slice(&chunk);
+
+        Ok(())
+    }
+
+    pub fn lock_question_content(ctx: Context<WriteQuestionContent>) -> Result<()> {
+        let question = &ctx.accounts.question;
+        let content = &mut ctx.accounts.question_content;
+
+        require!(!content.locked, VotingError::ContentLocked);
         require!(
-            reward >= MIN_REWARD_LAMPORTS,
-            VotingError::RewardTooSmall
+            Clock::get()?.unix_timestamp < question.commit_end_time,
+            VotingError::CommitPhaseEnded
+        );
+        require!(
+            std::str::from_utf8(&content.content).is_ok(),
+            VotingError::InvalidContent
         );
 
-        // Transfer reward from asker to vault
-        invoke(
-            &system_instruction::transfer(
-                &ctx.accounts.asker.key(),
-                &ctx.accounts.vault.key(),
-                reward,
-            ),
-            &[
-                ctx.accounts.asker.to_account_info(),
-                ctx.accounts.vault.to_account_info(),
-                ctx.accounts.system_program.to_account_info(),
-            ],
-        )?;
-        
-        // Initialize the question account.
+        content.locked = true;
+
+        msg!("Question content locked");
+
+        Ok(())
+    }
+
+    pub fn extend_question(ctx: Context<ExtendQuestion>) -> Result<()> {
+        let question = &mut ctx.accounts.question;
+
+        require!(
+            Clock::get()?.unix_timestamp >= question.commit_end_time,
+            VotingError::VotingStillActive
+        );
+        require!(can_extend(question), VotingError::CannotExtend);
+
+        question.commit_end_time += question.extension_delta;
+        question.reveal_end_time += question.extension_delta;
+        question.extensions_used += 1;
+
+        msg!(
+            "Question extended ({} of {}). Commit phase ends at {}, reveal phase at {}",
+            question.extensions_used,
+            question.max_extensions,
+            question.commit_end_time,
+            question.reveal_end_time
+        );
+
+        Ok(())
+    }
+
+    pub fn update_question(
+        ctx: Context<UpdateQuestion>,
+        question_text: String,
+        commit_end_time: i64,
+        reveal_end_time: i64,
+    ) -> Result<()> {
         let question = &mut ctx.accounts.question;
-        question.id = question_counter.count;
-        question.asker = *ctx.accounts.asker.key;
+
+        require!(question.committed_voters == 0, VotingError::QuestionHasCommitments);
+        require!(!question.reward_drained, VotingError::AlreadyDrained);
+        // A question that already resolved (e.g. to no quorum) keeps its outcome
+        require!(
+            !question.finalized
+                && !question.reward_fee_taken
+                && question.winning_option == 255,
+            VotingError::AlreadyFinalized
+        );
+
+        validate_question_schedule(&question_text, commit_end_time, reveal_end_time)?;
+
         question.question_text = question_text;
-        question.option_1 = "True".to_string();
-        question.option_2 = "False".to_string();
-        question.created_at = now;
         question.commit_end_time = commit_end_time;
         question.reveal_end_time = reveal_end_time;
-        question.votes_option_1 = 0;
-        question.votes_option_2 = 0;
-        question.finalized = false;
-        question.committed_voters = 0;
-        question.question_key = question_key;
-        question.winning_option = 255;
-        // For clarity, store the vault address in a dedicated field.
-        question.vault_address = ctx.accounts.vault.key();
-        question.claimed_weight = 0;
-        question.reward_drained = false;
-        
-        // Derive the bump for the question PDA.
-        let (_derived_pubkey, bump) = Pubkey::find_program_address(
-            &[b"question", ctx.accounts.asker.key.as_ref(), &question_counter.count.to_le_bytes()],
-            ctx.program_id,
+
+        emit!(QuestionUpdated {
+            question: question.key(),
+            question_text: question.question_text.clone(),
+            commit_end_time,
+            reveal_end_time,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_question(ctx: Context<CancelQuestion>) -> Result<()> {
+        let question = &ctx.accounts.question;
+
+        require!(question.committed_voters == 0, VotingError::QuestionHasCommitments);
+        require!(
+            !question.has_content || ctx.accounts.question_content.is_some(),
+            VotingError::MissingQuestionContent
         );
-        question.bump = bump;
-        
-        question_counter.count += 1;
-        
-        msg!("Question Created: {}", question.id);
-        msg!("Vault PDA: {}", ctx.accounts.vault.key());
+
+        // Closing the vault returns the reward along with its rent
+        msg!(
+            "Question cancelled. {} lamports refunded to {}",
+            ctx.accounts.vault.to_account_info().lamports(),
+            ctx.accounts.asker.key()
+        );
+
         Ok(())
     }
-    
-                        
 
     pub fn delete_expired_question(ctx: Context<DeleteExpiredQuestion>) -> Result<()> {
         let question = &ctx.accounts.question;
@@ -180,13 +599,21 @@ pub mod truth_network {
     
         let now = Clock::get()?.unix_timestamp;
 
+        // The content account is closed along with the question
+        require!(
+            !question.has_content || ctx.accounts.question_content.is_some(),
+            VotingError::MissingQuestionContent
+        );
 
         // Case 1: No one committed and commit phase is over
         let no_one_committed = question.committed_voters == 0 && now >= question.commit_end_time;
 
+        // Questions that can still be extended are not over yet
+        require!(!can_extend(question), VotingError::ExtensionAvailable);
+
         // Case 2: Reveal is over, but no one revealed or claimed
         let reveal_over = now >= question.reveal_end_time;
-        let no_votes_revealed = question.votes_option_1 == 0 && question.votes_option_2 == 0;
+        let no_votes_revealed = question.revealed_voters_count == 0;
         let all_rewards_claimed = question.total_distributed >= question.snapshot_reward;
         let all_rent_reclaimed = question.voter_records_closed == question.voter_records_count;
 
@@ -216,59 +643,64 @@ pub mod truth_network {
         
         // Verify that the passed question_id matches the one stored on the account.
         require!(question.id == question_id, VotingError::QuestionIdMismatch);
-        
+        require!(!question.finalized, VotingError::AlreadyFinalized);
+
+        // A conditional question is void as soon as its parent resolves the other way
+        if question.parent_question != Pubkey::default() {
+            let parent = ctx
+                .accounts
+                .parent_question
+                .as_ref()
+                .ok_or(VotingError::MissingParentQuestion)?;
+            require_keys_eq!(parent.key(), question.parent_question, VotingError::MissingParentQuestion);
+            require!(parent.finalized, VotingError::ParentNotFinalized);
+
+            if parent.winning_option != question.parent_outcome {
+                question.winning_option = OUTCOME_VOID;
+                question.finalized = true;
+                msg!(
+                    "Parent resolved to {} instead of {}. Question is void.",
+                    parent.winning_option,
+                    question.parent_outcome
+                );
+                return Ok(());
+            }
+        }
+
         require!(
             Clock::get()?.unix_timestamp >= question.reveal_end_time,
             VotingError::VotingStillActive
         );
-        require!(!question.finalized, VotingError::AlreadyFinalized);
+        // Questions that can still be extended are not over yet
+        require!(!can_extend(question), VotingError::ExtensionAvailable);
         
         
-        let total_votes = question.votes_option_1 + question.votes_option_2;
-        
-        // Calculate percentage for each option
-        let option1_percent = if total_votes > 0 {
-            (question.votes_option_1 as f64 / total_votes as f64) * 100.0
-        } else {
-            0.0
-        };
-        let option2_percent = if total_votes > 0 {
-            (question.votes_option_2 as f64 / total_votes as f64) * 100.0
-        } else {
-            0.0
-        };
+        let total_votes = total_votes(question);
 
         // Determine winning option and percentage
-        let (winning_option, winning_percent) = if total_votes == 0 {
-            (0, 0.0)
-        } else if question.votes_option_1 == question.votes_option_2 {
-            (0, 50.0)
-        } else if question.votes_option_1 > question.votes_option_2 {
-            (1, option1_percent)
-        } else {
-            (2, option2_percent)
-        };
-        
-        
-        
-        // Set eligible voters to the winning votes count
-        question.eligible_voters = if winning_option == 1 {
-            question.votes_option_1
-        } else {
-            question.votes_option_2
-        };
-    
+        let (winning_option, winning_percent) = tally_winner(question);
         
         question.winning_option = winning_option;
         question.winning_percent = winning_percent;
+        question.scalar_median = weighted_median(&question.scalar_reveals).unwrap_or(0);
         question.finalized = true;
+
+        // Set eligible voters to the winning votes count (everyone on a tie)
+        question.eligible_voters = rewarded_weight(question);
+
+        let slashed = slashed_stake(question);
+        if slashed > 0 {
+            msg!("Slashed {} lamports of losing stake into the winners' pool", slashed);
+        }
     
         
+        if question.question_type == QUESTION_TYPE_SCALAR {
+            msg!("Scalar result: weighted median {}", question.scalar_median);
+        }
         msg!(
-            "Voting Finalized. Total Votes: {}. Option 1: {} votes, Option 2: {} votes. Winning Option: {} with {:.0}% votes",
+            "Voting Finalized. Total Votes: {}. Votes per option: {:?}. Winning Option: {} with {:.0}% votes",
             total_votes,
-            question.votes_option_1,
-            question.votes_option_2,
+            &question.votes[..question.options.len()],
             winning_option,
             winning_percent,
         );
@@ -303,49 +735,168 @@ pub mod truth_network {
         Ok(())
     }
 
-    pub fn commit_vote(ctx: Context<CommitVote>, commitment: [u8; 32]) -> Result<()> {
+    pub fn commit_vote(
+        ctx: Context<CommitVote>,
+        commitment: [u8; 32],
+        revealer: Option<Pubkey>,
+    ) -> Result<()> {
         let question = &mut ctx.accounts.question;
         let voter_record = &mut ctx.accounts.voter_record;
+        let user_record = &mut ctx.accounts.user_record;
 
         require!(commitment != [0u8;32], VotingError::InvalidReveal);
     
         require!(Clock::get()?.unix_timestamp < question.commit_end_time, VotingError::CommitPhaseEnded);
 
+        require!(user_record.leave_requested_at == 0, VotingError::LeaveRequested);
+
+        // Conditional questions open once the parent resolved the required way
+        if question.parent_question != Pubkey::default() {
+            let parent = ctx
+                .accounts
+                .parent_question
+                .as_ref()
+                .ok_or(VotingError::MissingParentQuestion)?;
+            require_keys_eq!(parent.key(), question.parent_question, VotingError::MissingParentQuestion);
+            require!(parent.finalized, VotingError::ParentNotFinalized);
+            require!(
+                parent.winning_option == question.parent_outcome,
+                VotingError::ParentOutcomeMismatch
+            );
+        }
+
+        // Providers only commit once the long-form content can no longer change
+        if question.has_content {
+            let content = ctx
+                .accounts
+                .question_content
+                .as_ref()
+                .ok_or(VotingError::MissingQuestionContent)?;
+            require!(content.locked, VotingError::ContentNotLocked);
+        }
+
+        // Providers must have joined long enough before the question was created
+        require!(
+            user_record.created_at.saturating_add(question.min_membership_age) <= question.created_at,
+            VotingError::MembershipTooRecent
+        );
+
         require!(
             voter_record.commitment == [0u8; 32],
             VotingError::AlreadyVoted
         );
+
+        // Scalar votes are revealed by the voter through reveal_scalar_vote only
+        require!(
+            question.question_type != QUESTION_TYPE_SCALAR || revealer.is_none(),
+            VotingError::WrongQuestionType
+        );
+
+        // Scalar questions keep every reveal on the question account
+        require!(
+            question.question_type != QUESTION_TYPE_SCALAR
+                || question.committed_voters < MAX_SCALAR_REVEALS as u64,
+            VotingError::TooManyVoters
+        );
     
         voter_record.commitment = commitment;
         voter_record.voter = *ctx.accounts.voter.key;
         voter_record.question = question.key();
         voter_record.user_record_join_time = Clock::get()?.unix_timestamp;
+        voter_record.revealer = revealer.unwrap_or_default();
+
+        // Hold the commit bond (until reveal) and the vote stake (until settlement)
+        // in the question vault
+        let deposit = question.commit_bond + question.vote_stake;
+        if deposit > 0 {
+            invoke(
+                &system_instruction::transfer(
+                    &ctx.accounts.voter.key(),
+                    &ctx.accounts.vault.key(),
+                    deposit,
+                ),
+                &[
+                    ctx.accounts.voter.to_account_info(),
+                    ctx.accounts.vault.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                ],
+            )?;
+        }
+        voter_record.bond = question.commit_bond;
+        voter_record.stake = question.vote_stake;
     
         question.committed_voters += 1;
         question.voter_records_count += 1;
+        user_record.open_commitments += 1;
     
         msg!("Vote committed by {}", voter_record.voter);
         Ok(())
     }
-    
-    pub fn reveal_vote(ctx: Context<RevealVote>, password: String) -> Result<()> {
-        let question = &mut ctx.accounts.question;
+
+    pub fn recommit_vote(ctx: Context<RecommitVote>, commitment: [u8; 32]) -> Result<()> {
+        let question = &ctx.accounts.question;
+        let voter_record = &mut ctx.accounts.voter_record;
+
+        require!(commitment != [0u8;32], VotingError::InvalidReveal);
+        require!(Clock::get()?.unix_timestamp < question.commit_end_time, VotingError::CommitPhaseEnded);
+        require!(voter_record.commitment != [0u8; 32], VotingError::NotPartOfVoterList);
+        require!(!voter_record.revealed, VotingError::AlreadyRevealed);
+
+        voter_record.commitment = commitment;
+
+        msg!("Vote recommitted by {}", voter_record.voter);
+        Ok(())
+    }
+
+    pub fn withdraw_commitment(ctx: Context<WithdrawCommitment>) -> Result<()> {
+        let question = &mut ctx.accounts.question;
+        let voter_record = &ctx.accounts.voter_record;
+
+        require!(Clock::get()?.unix_timestamp < question.commit_end_time, VotingError::CommitPhaseEnded);
+        require!(voter_record.commitment != [0u8; 32], VotingError::NotPartOfVoterList);
+        require!(!voter_record.revealed, VotingError::AlreadyRevealed);
+
+        refund_bond(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.voter.to_account_info(),
+            voter_record,
+        )?;
+        transfer_from_vault(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.voter.to_account_info(),
+            voter_record.stake,
+        )?;
+
+        question.committed_voters -= 1;
+        question.voter_records_count -= 1;
+
+        let user_record = &mut ctx.accounts.user_record;
+        user_record.open_commitments = user_record.open_commitments.saturating_sub(1);
+
+        msg!("Commitment withdrawn by {}", voter_record.voter);
+        Ok(())
+    }
+    
+    pub fn reveal_vote(ctx: Context<RevealVote>, password: String) -> Result<()> {
+        let question_key = ctx.accounts.question.key();
+        let question = &mut ctx.accounts.question;
         let voter_record = &mut ctx.accounts.voter_record;
         let user_record = &mut ctx.accounts.user_record;
         
-        require!(
-            user_record.created_at <= voter_record.user_record_join_time,
-            VotingError::RejoinedAfterCommit
-        );        
-        require!(!voter_record.revealed, VotingError::AlreadyRevealed);
-        require!(Clock::get()?.unix_timestamp < question.reveal_end_time, VotingError::RevealPhaseEnded);
+        check_can_reveal(question, voter_record, user_record)?;
     
         let mut valid_vote: Option<u8> = None;
-        for vote in 1..=2 {
-            let input_data = format!("{}{}", vote, password);
-            let computed_hash = hash(input_data.as_bytes());
+        let candidates = (1..=question.options.len() as u8).chain([OPTION_INVALID]);
+        for vote in candidates.filter(|vote| is_valid_option(question, *vote)) {
+            let computed_hash = compute_commitment(
+                question.commitment_version,
+                &voter_record.voter,
+                &question_key,
+                vote,
+                password.as_bytes(),
+            );
     
-            if computed_hash.0 == voter_record.commitment {
+            if computed_hash == voter_record.commitment {
                 valid_vote = Some(vote);
                 break;
             }
@@ -353,32 +904,134 @@ pub mod truth_network {
     
         let vote = valid_vote.ok_or(VotingError::InvalidReveal)?;
     
-        voter_record.revealed = true;
-        voter_record.selected_option = vote;
-        voter_record.vote_weight = if user_record.reputation == 0 { 1 } else { user_record.reputation as u64 };
-    
-        if vote == 1 {
-            question.votes_option_1 += voter_record.vote_weight;
-        } else {
-            question.votes_option_2 += voter_record.vote_weight;
-        }
+        record_reveal(question, voter_record, user_record, vote)?;
+        refund_bond(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.voter.to_account_info(),
+            voter_record,
+        )
+    }
+
+    pub fn reveal_vote_salted(ctx: Context<RevealVote>, option: u8, salt: [u8; 32]) -> Result<()> {
+        let question_key = ctx.accounts.question.key();
+        reveal_with_salt(
+            &question_key,
+            &mut ctx.accounts.question,
+            &mut ctx.accounts.voter_record,
+            &mut ctx.accounts.user_record,
+            option,
+            &salt,
+        )?;
+        refund_bond(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.voter.to_account_info(),
+            &ctx.accounts.voter_record,
+        )
+    }
+
+    pub fn reveal_scalar_vote(ctx: Context<RevealVote>, value: i64, salt: [u8; 32]) -> Result<()> {
+        let question_key = ctx.accounts.question.key();
+        let question = &mut ctx.accounts.question;
+        let voter_record = &mut ctx.accounts.voter_record;
+        let user_record = &mut ctx.accounts.user_record;
+
+        check_can_reveal(question, voter_record, user_record)?;
+        require!(
+            question.question_type == QUESTION_TYPE_SCALAR,
+            VotingError::WrongQuestionType
+        );
+        require!(
+            value >= question.scalar_min && value <= question.scalar_max,
+            VotingError::ScalarOutOfRange
+        );
+
+        let computed_hash = compute_scalar_commitment(&voter_record.voter, &question_key, value, &salt);
+        require!(computed_hash == voter_record.commitment, VotingError::InvalidReveal);
 
-        // Increment revealed count
+        let weight = vote_weight(question, user_record);
+        voter_record.revealed = true;
+        voter_record.scalar_value = value;
+        voter_record.vote_weight = weight;
+        question.scalar_reveals.push(ScalarReveal { value, weight });
         question.revealed_voters_count += 1;
-    
-        // Update user revealed votes
-        user_record.total_revealed_votes += 1;
-    
-        // Recalculate reputation based on new revealed/correct votes
-        user_record.reputation = calculate_reputation(
-            user_record.total_revealed_votes,
-            user_record.total_correct_votes,
+
+        msg!("Vote Revealed Successfully! Value {}", value);
+        credit_reveals(user_record, 1);
+
+        refund_bond(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.voter.to_account_info(),
+            voter_record,
+        )
+    }
+
+    pub fn reveal_vote_delegated(
+        ctx: Context<RevealVoteDelegated>,
+        option: u8,
+        salt: [u8; 32],
+    ) -> Result<()> {
+        msg!(
+            "Revealing for {} via delegated revealer {}",
+            ctx.accounts.voter.key(),
+            ctx.accounts.revealer.key()
         );
-    
-        msg!("Vote Revealed Successfully! Option {}", vote);
-        msg!("New reputation: {}", user_record.reputation);
-    
-        Ok(())
+
+        let question_key = ctx.accounts.question.key();
+        reveal_with_salt(
+            &question_key,
+            &mut ctx.accounts.question,
+            &mut ctx.accounts.voter_record,
+            &mut ctx.accounts.user_record,
+            option,
+            &salt,
+        )?;
+        refund_bond(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.voter.to_account_info(),
+            &ctx.accounts.voter_record,
+        )
+    }
+
+    /// Reveals one entry per `(question, voter_record, vault)` triple in
+    /// `remaining_accounts`. Returns a status per entry: 0 when revealed,
+    /// otherwise the error code that entry failed with.
+    pub fn reveal_votes_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RevealVotesBatch<'info>>,
+        entries: Vec<RevealEntry>,
+    ) -> Result<Vec<u32>> {
+        let voter = ctx.accounts.voter.to_account_info();
+        let user_record = &mut ctx.accounts.user_record;
+        let remaining = ctx.remaining_accounts;
+
+        require!(!entries.is_empty(), VotingError::InvalidBatchEntry);
+        require!(
+            remaining.len() == entries.len() * 3,
+            VotingError::InvalidBatchEntry
+        );
+
+        // Every entry is weighted with the reputation held before the batch
+        let mut statuses = Vec::with_capacity(entries.len());
+        let mut revealed: u64 = 0;
+
+        for (index, (entry, accounts)) in entries.iter().zip(remaining.chunks(3)).enumerate() {
+            match reveal_batch_entry(ctx.program_id, &voter, user_record, entry, accounts) {
+                Ok(()) => {
+                    revealed += 1;
+                    statuses.push(0);
+                    msg!("Entry {}: vote revealed for question {}. Option {}", index, accounts[0].key(), entry.option);
+                }
+                Err(err) => {
+                    let code = batch_error_code(err);
+                    statuses.push(code);
+                    msg!("Entry {}: reveal failed for question {} with error {}", index, accounts[0].key(), code);
+                }
+            }
+        }
+
+        msg!("Batch reveal: {} of {} votes revealed", revealed, entries.len());
+        credit_reveals(user_record, revealed);
+
+        Ok(statuses)
     }
     
     
@@ -396,64 +1049,39 @@ pub mod truth_network {
         question.action_in_progress = true;
 
         let result = (|| {
+            // Bonds of revealed votes have all been returned once the reveal phase is over
+            require!(
+                Clock::get()?.unix_timestamp >= question.reveal_end_time,
+                VotingError::RevealPhaseNotOver
+            );
             require!(!voter_record.claimed, VotingError::AlreadyClaimed);
             require!(ctx.accounts.voter.key() == voter_record.voter, VotingError::NotEligible);
+            // Unrevealed votes go through reclaim, which releases the open commitment
+            require!(voter_record.revealed, VotingError::NotEligible);
+            require!(!can_extend(question), VotingError::ExtensionAvailable);
 
-            if question.winning_option == 255 {
-                question.winning_option = if question.votes_option_1 == question.votes_option_2 {
-                    0
-                } else if question.votes_option_1 > question.votes_option_2 {
-                    1
-                } else {
-                    2
-                };
-            }
+            resolve_winning_option(question);
+
+            require!(
+                question.winning_option != OUTCOME_CONTESTED,
+                VotingError::InsufficientMajority
+            );
 
-            let winning_option = question.winning_option;
-            let is_tie = winning_option == 0;
+            let is_tie = question.winning_option == 0;
+            let is_correct = !is_tie && is_rewarded_vote(question, voter_record);
 
             if !is_tie {
-                require!(
-                    voter_record.selected_option == winning_option,
-                    VotingError::NotEligible
-                );
+                require!(is_correct, VotingError::NotEligible);
+            }
+
+            if !question.reward_fee_taken {
+                take_reward_snapshot(question, &vault_info, &fee_receiver_info)?;
             }
 
             let rent = Rent::get()?;
             let min_balance = rent.minimum_balance(vault_info.data_len());
             let vault_balance = **vault_info.lamports.borrow();
 
-            if !question.reward_fee_taken {
-                let available_reward = vault_balance.saturating_sub(min_balance);
-                let fee = available_reward * 2 / 100;
-                let snapshot = available_reward.saturating_sub(fee);
-
-                **vault_info.try_borrow_mut_lamports()? -= fee;
-                **fee_receiver_info.try_borrow_mut_lamports()? += fee;
-
-                question.original_reward = available_reward;
-                question.snapshot_reward = snapshot;
-
-                question.snapshot_total_weight = if is_tie {
-                    question.votes_option_1 + question.votes_option_2
-                } else if winning_option == 1 {
-                    question.votes_option_1
-                } else {
-                    question.votes_option_2
-                };
-
-                question.claimed_weight = 0;
-                question.claimed_voters_count = 0;
-                question.claimed_remainder_count = 0;
-                question.total_distributed = 0;
-                question.reward_fee_taken = true;
-
-                msg!(
-                    "Reward snapshot initialized. Total weight: {}",
-                    question.snapshot_total_weight
-                );
-            }
-
             let voter_weight = voter_record.vote_weight;
             let total_snapshot_reward = question.snapshot_reward;
             let total_weight = question.snapshot_total_weight;
@@ -464,7 +1092,10 @@ pub mod truth_network {
             let base_share = (total_snapshot_reward * voter_weight) / total_weight;
 
             let mut voter_share = base_share;
-            let available = vault_balance.saturating_sub(min_balance);
+            let available = vault_balance
+                .saturating_sub(min_balance)
+                .saturating_sub(outstanding_stake(question))
+                .saturating_sub(pending_asker_refund(question));
 
             if is_last_claimer {
                 let remaining = total_snapshot_reward.saturating_sub(question.total_distributed);
@@ -483,9 +1114,35 @@ pub mod truth_network {
             question.claimed_voters_count += 1;
             question.voter_records_closed += 1;
 
+            // Delegators share in rewards earned with their stake
+            let delegator_share = if question.vote_weight_mode == VOTE_WEIGHT_REPUTATION_LOG_STAKE {
+                delegator_reward_share(user_record, voter_share, Clock::get()?.unix_timestamp)
+            } else {
+                0
+            };
+            if delegator_share > 0 {
+                let delegation_pool = ctx
+                    .accounts
+                    .delegation_pool
+                    .as_ref()
+                    .ok_or(VotingError::MissingDelegationPool)?;
+                transfer_from_vault(&vault_info, &delegation_pool.to_account_info(), delegator_share)?;
+
+                user_record.reward_per_delegated += delegator_share as u128
+                    * DELEGATION_REWARD_PRECISION
+                    / user_record.delegated_stake as u128;
+
+                msg!("{} lamports of the reward paid to delegators", delegator_share);
+            }
+            let voter_share = voter_share - delegator_share;
+
             **vault_info.try_borrow_mut_lamports()? -= voter_share;
             **voter_info.try_borrow_mut_lamports()? += voter_share;
 
+            let stake = stake_refund(question, voter_record);
+            transfer_from_vault(&vault_info, &voter_info, stake)?;
+            question.stake_returned += stake;
+
             // Store claim tx ID
             let tx_id_bytes = tx_id.as_bytes();
             let len = tx_id_bytes.len().min(64);
@@ -499,7 +1156,7 @@ pub mod truth_network {
                 .checked_add(voter_share)
                 .ok_or(VotingError::Overflow)?;
 
-            if !is_tie && voter_record.selected_option == winning_option {
+            if is_correct {
                 user_record.total_correct_votes += 1;
                 user_record.reputation = calculate_reputation(
                     user_record.total_revealed_votes,
@@ -509,10 +1166,8 @@ pub mod truth_network {
                 const MIN_VOTERS: u64 = 3;
                 const MIN_HOURS: i64 = 86_400;
 
-                let meets_conditions = !is_tie
-                    && question.revealed_voters_count >= MIN_VOTERS
-                    && question.reveal_end_time - question.created_at >= MIN_HOURS
-                    && voter_record.selected_option == winning_option;
+                let meets_conditions = question.revealed_voters_count >= MIN_VOTERS
+                    && question.reveal_end_time - question.created_at >= MIN_HOURS;
 
                 if meets_conditions {
                     user_record.invite_correct_votes += 1;
@@ -546,15 +1201,27 @@ pub mod truth_network {
         let fee_receiver = &ctx.accounts.fee_receiver;
 
         require!(!question.reward_drained, VotingError::AlreadyDrained);
+        // A conditional question may still turn out void and owe the asker a refund
+        require!(
+            question.parent_question == Pubkey::default()
+                || (question.finalized && question.winning_option != OUTCOME_VOID),
+            VotingError::CannotDrainReward
+        );
     
         let now = Clock::get()?.unix_timestamp;
+
+        // Questions that can still be extended are not over yet
+        require!(!can_extend(question), VotingError::ExtensionAvailable);
     
-        // Allow draining if commit phase ended and no commits
-        let can_drain_due_to_no_commit = now >= question.commit_end_time && question.committed_voters == 0;
+        // Allow draining if commit phase ended and no commits, or if reveal phase
+        // ended and no one revealed. With a quorum set the question resolves to
+        // OUTCOME_NO_QUORUM and the asker is refunded instead
+        let can_drain_due_to_no_commit =
+            now >= question.commit_end_time && question.committed_voters == 0 && !has_quorum(question);
     
-        // Or if reveal phase ended and no one revealed
-        let no_votes_revealed = question.votes_option_1 == 0 && question.votes_option_2 == 0;
-        let can_drain_due_to_no_reveal = now >= question.reveal_end_time && no_votes_revealed;
+        let no_votes_revealed = question.revealed_voters_count == 0;
+        let can_drain_due_to_no_reveal =
+            now >= question.reveal_end_time && no_votes_revealed && !has_quorum(question);
     
         require!(
             can_drain_due_to_no_commit || can_drain_due_to_no_reveal,
@@ -580,6 +1247,41 @@ pub mod truth_network {
         Ok(())
     }
 
+    pub fn claim_asker_refund(ctx: Context<ClaimAskerRefund>) -> Result<()> {
+        let question = &mut ctx.accounts.question;
+        let vault_info = ctx.accounts.vault.to_account_info();
+
+        require!(
+            Clock::get()?.unix_timestamp >= question.reveal_end_time
+                || question.winning_option == OUTCOME_VOID,
+            VotingError::RevealPhaseNotOver
+        );
+        require!(!question.asker_refund_claimed, VotingError::AlreadyClaimed);
+        // An extendable question has not resolved yet
+        require!(!can_extend(question), VotingError::ExtensionAvailable);
+        // Only finalize_voting can tell whether a conditional question is void
+        require!(
+            question.finalized || question.parent_question == Pubkey::default(),
+            VotingError::NotFinalized
+        );
+
+        resolve_winning_option(question);
+
+        if !question.reward_fee_taken {
+            take_reward_snapshot(question, &vault_info, &ctx.accounts.fee_receiver.to_account_info())?;
+        }
+
+        let refund = question.asker_refund;
+        require!(refund > 0, VotingError::NoAskerRefund);
+
+        transfer_from_vault(&vault_info, &ctx.accounts.asker.to_account_info(), refund)?;
+        question.asker_refund_claimed = true;
+
+        msg!("Refunded {} lamports to the asker", refund);
+
+        Ok(())
+    }
+
     pub fn reclaim_commit_or_loser_rent(ctx: Context<ReclaimCommitOrLoserRent>) -> Result<()> {
         let question = &mut ctx.accounts.question;
         let voter_record = &ctx.accounts.voter_record;
@@ -591,19 +1293,38 @@ pub mod truth_network {
 
         // Must not have claimed
         require!(!voter_record.claimed, VotingError::AlreadyClaimed);
+        // An extendable question has not resolved yet
+        require!(!can_extend(question), VotingError::ExtensionAvailable);
+
+        resolve_winning_option(question);
 
-        let winning_option = question.winning_option;
-        let selected_option = voter_record.selected_option;
         let revealed = voter_record.revealed;
 
         // Either: not revealed, or revealed but voted incorrectly or tie
         let can_reclaim = 
-            !revealed ||
-            winning_option == 0 || // tie case
-            selected_option != winning_option;
+            question.winning_option == 0 || // tie case
+            !is_rewarded_vote(question, voter_record);
 
         require!(can_reclaim, VotingError::AlreadyEligibleOrWinner);
 
+        // An unrevealed vote is still counted as open on the user's record,
+        // unless the user has already left
+        let user_record_info = ctx.accounts.user_record.to_account_info();
+        if !revealed && user_record_info.owner == &crate::ID && !user_record_info.data_is_empty() {
+            let mut user_record = UserRecord::try_deserialize(&mut &user_record_info.try_borrow_data()?[..])?;
+            user_record.open_commitments = user_record.open_commitments.saturating_sub(1);
+            user_record.try_serialize(&mut &mut user_record_info.try_borrow_mut_data()?[..])?;
+        }
+
+        // Revealed losers get their stake back minus the slash; ties are not slashed
+        let stake = stake_refund(question, voter_record);
+        transfer_from_vault(
+            &ctx.accounts.vault.to_account_info(),
+            &ctx.accounts.voter.to_account_info(),
+            stake,
+        )?;
+        question.stake_returned += stake;
+
         question.voter_records_closed += 1;
 
         msg!(
@@ -614,6 +1335,28 @@ pub mod truth_network {
         Ok(())
     }   
 
+    pub fn release_orphaned_commitment(ctx: Context<ReleaseOrphanedCommitment>) -> Result<()> {
+        let voter_record = &ctx.accounts.voter_record;
+        let user_record = &mut ctx.accounts.user_record;
+
+        // Only for records whose question was deleted before they were reclaimed
+        require!(
+            ctx.accounts.question.data_is_empty(),
+            VotingError::QuestionStillExists
+        );
+
+        if !voter_record.revealed {
+            user_record.open_commitments = user_record.open_commitments.saturating_sub(1);
+        }
+
+        msg!(
+            "Released commitment of {} on deleted question {}",
+            ctx.accounts.voter.key(),
+            voter_record.question
+        );
+        Ok(())
+    }
+
     pub fn nominate_invitee(ctx: Context<NominateInvitee>, nominee: Pubkey) -> Result<()> {
         let invite = &mut ctx.accounts.invite;
         let user_record = &mut ctx.accounts.user_record;
@@ -650,6 +1393,29 @@ pub mod truth_network {
         Ok(())
     }
 
+    pub fn set_network_config(
+        ctx: Context<SetNetworkConfig>,
+        provider_deposit: u64,
+        unbonding_period: i64,
+        default_min_membership_age: i64,
+    ) -> Result<()> {
+        require!(unbonding_period >= 0, VotingError::InvalidTimeframe);
+        require!(default_min_membership_age >= 0, VotingError::InvalidMembershipAge);
+
+        let network_config = &mut ctx.accounts.network_config;
+        network_config.provider_deposit = provider_deposit;
+        network_config.unbonding_period = unbonding_period;
+        network_config.default_min_membership_age = default_min_membership_age;
+
+        msg!(
+            "Network config updated. Provider deposit: {} lamports, unbonding period: {} seconds, default membership age: {} seconds",
+            provider_deposit,
+            unbonding_period,
+            default_min_membership_age
+        );
+        Ok(())
+    }
+
     pub fn delete_invite(ctx: Context<DeleteInvite>) -> Result<()> {
         msg!(
             "Invite closed by inviter {} for invitee {}",
@@ -658,212 +1424,1663 @@ pub mod truth_network {
         );
         Ok(())
     }
-    
-     
-}
 
-fn calculate_reputation(revealed: u64, correct: u64) -> u8 {
-    let sum = revealed + correct;
+    pub fn migrate_network_config(ctx: Context<MigrateNetworkConfig>) -> Result<()> {
+        let network_config = ctx.accounts.network_config.to_account_info();
 
-    match sum {
-        0 | 1 => 1,
-        2 => 2,
-        3 => 3,
-        4 => 4,
-        5 => 5,
-        6 => 6,
-        7..=9 => 7,
-        10..=12 => 8,
-        13..=17 => 9,
-        18..=25 => 10,
-        26..=34 => 11,
-        35..=46 => 12,
-        47..=62 => 13,
-        63..=87 => 14,
-        88..=118 => 15,
-        119..=163 => 16,
-        164..=224 => 17,
-        225..=308 => 18,
-        _ => 19,
-    }
-}
+        require!(
+            network_config.try_borrow_data()?[..8] == NetworkConfig::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(network_config.data_len() < NETWORK_CONFIG_SPACE, VotingError::AlreadyMigrated);
+
+        // New fields start at 0 until set_network_config sets them
+        grow_account(
+            &network_config,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            NETWORK_CONFIG_SPACE,
+        )?;
 
+        msg!("Network config migrated");
+        Ok(())
+    }
 
-#[account]
-pub struct Question {
-    pub id: u64,
-    pub asker: Pubkey,
-    pub question_key: Pubkey,
-    pub vault_address: Pubkey,
-    pub question_text: String,
-    pub option_1: String,
-    pub option_2: String,
-    pub created_at: i64,
-    pub commit_end_time: i64,
-    pub reveal_end_time: i64,
-    pub votes_option_1: u64,
-    pub votes_option_2: u64,
-    pub finalized: bool,
-    pub committed_voters: u64,
-    pub revealed_voters_count: u64,
-    pub eligible_voters: u64,
-    pub winning_option: u8,
-    pub winning_percent: f64,
-    pub reward_fee_taken: bool,
-    pub snapshot_reward: u64,
-    pub original_reward: u64,
-    pub claimed_remainder_count: u64,
-    pub snapshot_total_weight: u64,
-    pub total_distributed: u64,
-    pub claimed_voters_count: u64,
-    pub claimed_weight: u64,
-    pub voter_records_count: u64,
-    pub voter_records_closed: u64,
-    pub reward_drained: bool,
-    pub action_in_progress: bool,
-    pub bump: u8,
-}
+    pub fn migrate_question(ctx: Context<MigrateQuestion>) -> Result<()> {
+        let question_info = ctx.accounts.question.to_account_info();
 
-#[derive(AnchorSerialize, AnchorDeserialize)]
-pub struct WinnerResult {
-    pub total_votes: u64,
-    pub votes_option1: u64,
-    pub votes_option2: u64,
-    pub winning_option: u8,
-    pub winning_percent: f64,
-}
+        require!(
+            question_info.try_borrow_data()?[..8] == Question::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(
+            question_info.data_len() <= LEGACY_QUESTION_SPACE,
+            VotingError::AlreadyMigrated
+        );
 
+        let legacy = LegacyQuestion::deserialize(&mut &question_info.try_borrow_data()?[8..])?;
 
-#[derive(Accounts)]
-#[instruction(question_text: String)]
-pub struct CreateQuestion<'info> {
-    #[account(
-        mut,
-        seeds = [b"question_counter", asker.key().as_ref()],
-        bump,
-        has_one = asker
-    )]
-    pub question_counter: Account<'info, QuestionCounter>,
+        let expected_key = Pubkey::create_program_address(
+            &[
+                b"question",
+                legacy.asker.as_ref(),
+                &legacy.id.to_le_bytes(),
+                &[legacy.bump],
+            ],
+            ctx.program_id,
+        )
+        .map_err(|_| anchor_lang::error::ErrorCode::ConstraintSeeds)?;
+        require_keys_eq!(
+            expected_key,
+            question_info.key(),
+            anchor_lang::error::ErrorCode::ConstraintSeeds
+        );
 
-    #[account(
-        init,
-        payer = asker,
-        space = 450,
-        seeds = [b"question", asker.key().as_ref(), &question_counter.count.to_le_bytes()],
-        bump
-    )]
-    pub question: Account<'info, Question>, 
+        let mut votes = [0u64; MAX_OPTIONS];
+        votes[0] = legacy.votes_option_1;
+        votes[1] = legacy.votes_option_2;
+
+        // Everything added since keeps its default: commitment version 0
+        // (legacy), no bond, stake, quorum or parent
+        let question = Question {
+            id: legacy.id,
+            asker: legacy.asker,
+            question_key: legacy.question_key,
+            vault_address: legacy.vault_address,
+            question_text: legacy.question_text,
+            options: vec![legacy.option_1, legacy.option_2],
+            created_at: legacy.created_at,
+            commit_end_time: legacy.commit_end_time,
+            reveal_end_time: legacy.reveal_end_time,
+            votes,
+            finalized: legacy.finalized,
+            committed_voters: legacy.committed_voters,
+            revealed_voters_count: legacy.revealed_voters_count,
+            eligible_voters: legacy.eligible_voters,
+            winning_option: legacy.winning_option,
+            winning_percent: legacy.winning_percent,
+            reward_fee_taken: legacy.reward_fee_taken,
+            snapshot_reward: legacy.snapshot_reward,
+            original_reward: legacy.original_reward,
+            claimed_remainder_count: legacy.claimed_remainder_count,
+            snapshot_total_weight: legacy.snapshot_total_weight,
+            total_distributed: legacy.total_distributed,
+            claimed_voters_count: legacy.claimed_voters_count,
+            claimed_weight: legacy.claimed_weight,
+            voter_records_count: legacy.voter_records_count,
+            voter_records_closed: legacy.voter_records_closed,
+            reward_drained: legacy.reward_drained,
+            action_in_progress: legacy.action_in_progress,
+            bump: legacy.bump,
+            commitment_version: COMMITMENT_VERSION_LEGACY,
+            ..Default::default()
+        };
 
-    // Change vault from UncheckedAccount to Account<Vault>
-    #[account(
-        init,
-        payer = asker,
-        space = 8,  // Minimal space for Vault (only the 8-byte discriminator).
-        seeds = [b"vault", question.key().as_ref()],
-        bump
-    )]
-    pub vault: Account<'info, Vault>,
+        grow_account(
+            &question_info,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            question_space(&question.question_text),
+        )?;
+        question.try_serialize(&mut &mut question_info.try_borrow_mut_data()?[..])?;
+
+        msg!("Question {} migrated", question.id);
+        Ok(())
+    }
+
+    pub fn migrate_voter_record(ctx: Context<MigrateVoterRecord>) -> Result<()> {
+        let voter_record = ctx.accounts.voter_record.to_account_info();
+
+        require!(
+            voter_record.try_borrow_data()?[..8] == VoterRecord::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(voter_record.data_len() < VOTER_RECORD_SPACE, VotingError::AlreadyMigrated);
+
+        // Fields added since are appended, and zeroes decode as "not set"
+        grow_account(
+            &voter_record,
+            &ctx.accounts.payer.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            VOTER_RECORD_SPACE,
+        )?;
+
+        msg!("Voter record {} migrated", voter_record.key());
+        Ok(())
+    }
+
+    pub fn migrate_user_record(ctx: Context<MigrateUserRecord>) -> Result<()> {
+        let user_record = ctx.accounts.user_record.to_account_info();
+
+        require!(
+            user_record.try_borrow_data()?[..8] == UserRecord::DISCRIMINATOR,
+            anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch
+        );
+        require!(user_record.data_len() < USER_RECORD_SPACE, VotingError::AlreadyMigrated);
+
+        // Members from before provider stake existed keep a stake of 0; the
+        // new stake vault lets them add stake and leave like everyone else
+        grow_account(
+            &user_record,
+            &ctx.accounts.user.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            USER_RECORD_SPACE,
+        )?;
+
+        msg!("User record of {} migrated", ctx.accounts.user.key());
+        Ok(())
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn init_question(
+    ctx: Context<CreateQuestion>,
+    question_text: String,
+    options: Vec<String>,
+    scalar: Option<ScalarRange>,
+    reward: u64,
+    commit_end_time: i64,
+    reveal_end_time: i64,
+    settings: QuestionSettings,
+) -> Result<()> {
+    let question_counter = &mut ctx.accounts.question_counter;
+    let question_key = ctx.accounts.question.key();
+
+    validate_question_schedule(&question_text, commit_end_time, reveal_end_time)?;
+    let now = Clock::get()?.unix_timestamp;
+
+    if scalar.is_none() {
+        require!(
+            options.len() >= 2 && options.len() <= MAX_OPTIONS,
+            VotingError::InvalidOptionCount
+        );
+        for label in &options {
+            require!(
+                !label.is_empty() && label.len() <= MAX_OPTION_LABEL_LEN,
+                VotingError::InvalidOptionLabel
+            );
+        }
+    }
+    
+    // Require reward to be at least 0.05 SOL (in lamports)
+    const MIN_REWARD_LAMPORTS: u64 = 50_000_000; // 0.05 SOL
+    require!(
+        reward >= MIN_REWARD_LAMPORTS,
+        VotingError::RewardTooSmall
+    );
+
+    let min_membership_age = settings
+        .min_membership_age
+        .unwrap_or(ctx.accounts.network_config.default_min_membership_age);
+    require!(min_membership_age >= 0, VotingError::InvalidMembershipAge);
+    require!(settings.slash_rate_bps <= 10_000, VotingError::InvalidSlashRate);
+    require!(
+        settings.vote_weight_mode == VOTE_WEIGHT_REPUTATION
+            || settings.vote_weight_mode == VOTE_WEIGHT_REPUTATION_LOG_STAKE,
+        VotingError::InvalidVoteWeightMode
+    );
+    require!(
+        settings.winning_rule == WINNING_RULE_PLURALITY
+            || settings.winning_rule == WINNING_RULE_MAJORITY,
+        VotingError::InvalidWinningRule
+    );
+    require!(
+        settings.required_majority_bps <= 10_000,
+        VotingError::InvalidMajorityThreshold
+    );
+    // The majority rule already sets a threshold; a second one would make a
+    // winner short of it resolve either tied or contested
+    require!(
+        settings.winning_rule != WINNING_RULE_MAJORITY || settings.required_majority_bps == 0,
+        VotingError::InvalidMajorityThreshold
+    );
+
+    require!(
+        settings.extension_delta >= 0
+            && (settings.extension_delta == 0
+                || (settings.max_extensions > 0 && settings.extension_threshold > 0)),
+        VotingError::InvalidExtensionPolicy
+    );
+
+    if let Some(parent_outcome) = settings.parent_outcome {
+        let parent = ctx
+            .accounts
+            .parent_question
+            .as_ref()
+            .ok_or(VotingError::MissingParentQuestion)?;
+        require!(
+            is_possible_outcome(parent, parent_outcome),
+            VotingError::InvalidParentOutcome
+        );
+        // Commits only open once the parent is finalized
+        require!(
+            commit_end_time > parent.reveal_end_time,
+            VotingError::CommitEndsBeforeParent
+        );
+        ctx.accounts.question.parent_question = parent.key();
+        ctx.accounts.question.parent_outcome = parent_outcome;
+    } else {
+        require!(
+            ctx.accounts.parent_question.is_none(),
+            VotingError::UnexpectedParentQuestion
+        );
+    }
+    for uri in [&settings.resolution_criteria_uri, &settings.source_uri].into_iter().flatten() {
+        require!(uri.len() <= MAX_URI_LEN, VotingError::UriTooLong);
+    }
+    // Criteria published off chain must be pinned by their hash
+    require!(
+        settings.resolution_criteria_uri.is_none() || settings.resolution_criteria_hash.is_some(),
+        VotingError::MissingCriteriaHash
+    );
+
+    // Transfer reward from asker to vault
+    invoke(
+        &system_instruction::transfer(
+            &ctx.accounts.asker.key(),
+            &ctx.accounts.vault.key(),
+            reward,
+        ),
+        &[
+            ctx.accounts.asker.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+        ],
+    )?;
+    
+    // Initialize the question account.
+    let question = &mut ctx.accounts.question;
+    question.id = question_counter.count;
+    question.asker = *ctx.accounts.asker.key;
+    question.question_text = question_text;
+    question.options = options;
+    question.created_at = now;
+    question.commit_end_time = commit_end_time;
+    question.reveal_end_time = reveal_end_time;
+    question.votes = [0; MAX_OPTIONS];
+    question.finalized = false;
+    question.committed_voters = 0;
+    question.question_key = question_key;
+    question.winning_option = 255;
+    // For clarity, store the vault address in a dedicated field.
+    question.vault_address = ctx.accounts.vault.key();
+    question.claimed_weight = 0;
+    question.reward_drained = false;
+    question.commitment_version = COMMITMENT_VERSION_BOUND;
+    question.commit_bond = settings.commit_bond;
+    question.min_membership_age = min_membership_age;
+    question.vote_stake = settings.vote_stake;
+    question.slash_rate_bps = settings.slash_rate_bps;
+    question.vote_weight_mode = settings.vote_weight_mode;
+    question.winning_rule = settings.winning_rule;
+    question.min_revealed_voters = settings.min_revealed_voters;
+    question.min_revealed_weight = settings.min_revealed_weight;
+    question.required_majority_bps = settings.required_majority_bps;
+    question.extension_delta = settings.extension_delta;
+    question.max_extensions = settings.max_extensions;
+    question.extension_threshold = settings.extension_threshold;
+    question.resolution_criteria_hash = settings.resolution_criteria_hash;
+    question.resolution_criteria_uri = settings.resolution_criteria_uri;
+    question.source_uri = settings.source_uri;
+    question.event_time = settings.event_time;
+    if let Some(range) = scalar {
+        question.question_type = QUESTION_TYPE_SCALAR;
+        question.scalar_min = range.min_value;
+        question.scalar_max = range.max_value;
+        question.scalar_decimals = range.decimals;
+        question.scalar_tolerance = range.tolerance;
+    } else {
+        question.question_type = QUESTION_TYPE_OPTIONS;
+    }
+    
+    // Derive the bump for the question PDA.
+    let (_derived_pubkey, bump) = Pubkey::find_program_address(
+        &[b"question", ctx.accounts.asker.key.as_ref(), &question_counter.count.to_le_bytes()],
+        ctx.program_id,
+    );
+    question.bump = bump;
+    
+    question_counter.count += 1;
+    
+    msg!("Question Created: {}", question.id);
+    msg!("Vault PDA: {}", ctx.accounts.vault.key());
+    Ok(())
+}
+
+/// Checks shared by question creation and `update_question`.
+fn validate_question_schedule(question_text: &str, commit_end_time: i64, reveal_end_time: i64) -> Result<()> {
+    // Minimum length check for question text
+    require!(
+        question_text.len() >= 10,
+        VotingError::QuestionTooShort
+    );
+
+    // Maximum length check for question text
+    require!(
+        question_text.len() <= 150,
+        VotingError::QuestionTooLong
+    );
+
+    // Ensure commit and reveal times are valid.
+    let now = Clock::get()?.unix_timestamp;
+    require!(now < commit_end_time, VotingError::VotingEnded);
+    require!(commit_end_time < reveal_end_time, VotingError::InvalidTimeframe);
+
+    Ok(())
+}
+
+// Serialized size of the question's fixed-width fields
+const QUESTION_FIXED_LEN: usize = 574;
+
+fn question_space(question_text: &str) -> usize {
+    // A question holds either option labels or scalar reveals, never both
+    let list_len = (MAX_OPTIONS * (4 + MAX_OPTION_LABEL_LEN)).max(MAX_SCALAR_REVEALS * 16);
+    let uris_len = 2 * (1 + 4 + MAX_URI_LEN);
+    8 + QUESTION_FIXED_LEN + 4 + question_text.len() + 4 + 4 + list_len + uris_len
+}
+
+fn resolve_winning_option(question: &mut Question) {
+    if question.winning_option == 255 {
+        question.winning_option = tally_winner(question).0;
+        question.scalar_median = weighted_median(&question.scalar_reveals).unwrap_or(0);
+    }
+}
+
+fn total_votes(question: &Question) -> u64 {
+    question.votes.iter().sum::<u64>() + question.invalid_votes
+}
+
+/// Winning option (0 on a tie or when nobody voted) and its share of the
+/// revealed weight, under the question's quorum, winning rule and required
+/// majority.
+fn tally_winner(question: &Question) -> (u8, f64) {
+    if !quorum_met(question) {
+        return (OUTCOME_NO_QUORUM, 0.0);
+    }
+
+    let (winner, top_weight) = if question.question_type == QUESTION_TYPE_SCALAR {
+        scalar_winner(question)
+    } else {
+        option_winner(question)
+    };
+    let total_weight = revealed_weight(question);
+    let percent = if total_weight == 0 {
+        0.0
+    } else {
+        (top_weight as f64 / total_weight as f64) * 100.0
+    };
+
+    // Compared in integers so a winner exactly at the threshold is not contested
+    if winner != 0
+        && top_weight as u128 * 10_000 < total_weight as u128 * question.required_majority_bps as u128
+    {
+        return (OUTCOME_CONTESTED, percent);
+    }
+    (winner, percent)
+}
+
+/// Winning option (0 on a tie) and the weight behind it.
+fn option_winner(question: &Question) -> (u8, u64) {
+    let total_votes = total_votes(question);
+    if total_votes == 0 {
+        return (0, 0);
+    }
+
+    // The invalid outcome competes like any other option
+    let option_votes = &question.votes[..question.options.len()];
+    let top_votes = *option_votes.iter().max().unwrap_or(&0);
+    let top_votes = top_votes.max(question.invalid_votes);
+
+    let leaders = option_votes
+        .iter()
+        .chain([&question.invalid_votes])
+        .filter(|votes| **votes == top_votes)
+        .count();
+    if leaders > 1 {
+        return (0, top_votes);
+    }
+    if question.winning_rule == WINNING_RULE_MAJORITY && top_votes * 2 <= total_votes {
+        return (0, top_votes);
+    }
+
+    match option_votes.iter().position(|votes| *votes == top_votes) {
+        Some(winner) => (winner as u8 + 1, top_votes),
+        None => (OPTION_INVALID, top_votes),
+    }
+}
+
+/// Whether the question can resolve to `outcome`: one of its answers, a tie,
+/// or an outcome its own settings make possible.
+fn is_possible_outcome(question: &Question, outcome: u8) -> bool {
+    let answer = if question.question_type == QUESTION_TYPE_SCALAR {
+        outcome == 1
+    } else {
+        (1..=question.options.len()).contains(&(outcome as usize)) || outcome == OPTION_INVALID
+    };
+    answer
+        || outcome == 0
+        || (outcome == OUTCOME_NO_QUORUM && has_quorum(question))
+        || (outcome == OUTCOME_CONTESTED && question.required_majority_bps > 0)
+        || (outcome == OUTCOME_VOID && question.parent_question != Pubkey::default())
+}
+
+/// Whether the asker's extension policy still applies: too few commits, no
+/// reveals yet and extensions left.
+fn can_extend(question: &Question) -> bool {
+    question.extension_delta > 0
+        && question.extensions_used < question.max_extensions
+        && question.committed_voters < question.extension_threshold
+        && question.revealed_voters_count == 0
+        && !question.finalized
+        && question.winning_option == 255
+        && !question.reward_fee_taken
+}
+
+fn has_quorum(question: &Question) -> bool {
+    question.min_revealed_voters > 0 || question.min_revealed_weight > 0
+}
+
+fn quorum_met(question: &Question) -> bool {
+    question.revealed_voters_count >= question.min_revealed_voters
+        && revealed_weight(question) >= question.min_revealed_weight
+}
+
+fn revealed_weight(question: &Question) -> u64 {
+    if question.question_type == QUESTION_TYPE_SCALAR {
+        question.scalar_reveals.iter().map(|reveal| reveal.weight).sum()
+    } else {
+        total_votes(question)
+    }
+}
+
+/// Scalar questions resolve to 1 once anyone revealed (0 otherwise), with the
+/// revealed weight that landed within tolerance of the median.
+fn scalar_winner(question: &Question) -> (u8, u64) {
+    let Some(median) = weighted_median(&question.scalar_reveals) else {
+        return (0, 0);
+    };
+
+    (1, scalar_weight_within(question, median))
+}
+
+/// Lower weighted median of the revealed values.
+fn weighted_median(reveals: &[ScalarReveal]) -> Option<i64> {
+    let total_weight: u64 = reveals.iter().map(|reveal| reveal.weight).sum();
+    if total_weight == 0 {
+        return None;
+    }
+
+    let mut sorted = reveals.to_vec();
+    sorted.sort_by_key(|reveal| reveal.value);
+
+    let mut cumulative = 0u64;
+    for reveal in &sorted {
+        cumulative += reveal.weight;
+        if cumulative * 2 >= total_weight {
+            return Some(reveal.value);
+        }
+    }
+    None
+}
+
+fn within_tolerance(question: &Question, median: i64, value: i64) -> bool {
+    (value as i128 - median as i128).unsigned_abs() <= question.scalar_tolerance as u128
+}
+
+fn scalar_weight_within(question: &Question, median: i64) -> u64 {
+    question
+        .scalar_reveals
+        .iter()
+        .filter(|reveal| within_tolerance(question, median, reveal.value))
+        .map(|reveal| reveal.weight)
+        .sum()
+}
+
+/// Whether a revealed vote shares the reward pool: it picked the winning
+/// option (or a scalar value within tolerance of the median), or the
+/// question tied and every revealer shares.
+fn is_rewarded_vote(question: &Question, voter_record: &VoterRecord) -> bool {
+    if !voter_record.revealed {
+        return false;
+    }
+    match question.winning_option {
+        0 => true,
+        OUTCOME_NO_QUORUM | OUTCOME_CONTESTED | OUTCOME_VOID => false,
+        _ if question.question_type == QUESTION_TYPE_SCALAR => {
+            within_tolerance(question, question.scalar_median, voter_record.scalar_value)
+        }
+        winner => voter_record.selected_option == winner,
+    }
+}
+
+/// Total vote weight sharing the reward pool.
+fn rewarded_weight(question: &Question) -> u64 {
+    match question.winning_option {
+        0 | 255 => total_votes(question),
+        OUTCOME_NO_QUORUM | OUTCOME_CONTESTED | OUTCOME_VOID => 0,
+        OPTION_INVALID => question.invalid_votes,
+        _ if question.question_type == QUESTION_TYPE_SCALAR => {
+            scalar_weight_within(question, question.scalar_median)
+        }
+        winner => question.votes[winner as usize - 1],
+    }
+}
+
+fn revealed_stake_total(question: &Question) -> u64 {
+    if question.question_type == QUESTION_TYPE_SCALAR {
+        question.scalar_reveals.len() as u64 * question.vote_stake
+    } else {
+        question.revealed_stake.iter().sum::<u64>() + question.invalid_stake
+    }
+}
+
+fn is_valid_option(question: &Question, option: u8) -> bool {
+    if question.question_type == QUESTION_TYPE_SCALAR {
+        return false;
+    }
+    option == OPTION_INVALID || (option >= 1 && option as usize <= question.options.len())
+}
+
+fn slash_per_vote(question: &Question) -> u64 {
+    (question.vote_stake as u128 * question.slash_rate_bps as u128 / 10_000) as u64
+}
+
+/// Stake taken from revealers who voted against the winning option.
+/// Every vote on a question posts the same stake, so this matches the sum
+/// of the per-voter slashes applied in `stake_refund`.
+fn slashed_stake(question: &Question) -> u64 {
+    if question.vote_stake == 0 || !slashes_losers(question) {
+        return 0;
+    }
+    let losing_votes = match question.winning_option {
+        _ if question.question_type == QUESTION_TYPE_SCALAR => question
+            .scalar_reveals
+            .iter()
+            .filter(|reveal| !within_tolerance(question, question.scalar_median, reveal.value))
+            .count() as u64,
+        OPTION_INVALID => (revealed_stake_total(question) - question.invalid_stake) / question.vote_stake,
+        winner => {
+            let losing_stake = revealed_stake_total(question) - question.revealed_stake[winner as usize - 1];
+            losing_stake / question.vote_stake
+        }
+    };
+    losing_votes * slash_per_vote(question)
+}
+
+/// Whether revealers who did not vote for the outcome lose part of their stake.
+/// Ties and outcomes without a winner return stake in full.
+fn slashes_losers(question: &Question) -> bool {
+    !matches!(
+        question.winning_option,
+        0 | 255 | OUTCOME_NO_QUORUM | OUTCOME_CONTESTED | OUTCOME_VOID
+    )
+}
+
+/// Revealed stake that still has to be paid back from the vault.
+fn outstanding_stake(question: &Question) -> u64 {
+    revealed_stake_total(question)
+        .saturating_sub(slashed_stake(question))
+        .saturating_sub(question.stake_returned)
+}
+
+/// Share of the reward, after the fee, refunded to the asker rather than paid
+/// to voters.
+fn asker_refund_bps(question: &Question) -> u64 {
+    match question.winning_option {
+        OUTCOME_NO_QUORUM | OUTCOME_CONTESTED | OUTCOME_VOID => 10_000,
+        OPTION_INVALID => 10_000 - INVALID_REWARD_BPS,
+        _ => 0,
+    }
+}
+
+/// Asker refund still sitting in the vault.
+fn pending_asker_refund(question: &Question) -> u64 {
+    if question.asker_refund_claimed {
+        0
+    } else {
+        question.asker_refund
+    }
+}
+
+/// Splits the reward into the fee, the voters' pool and the asker's refund.
+fn split_reward(question: &Question, available_reward: u64) -> (u64, u64, u64) {
+    let refund_bps = asker_refund_bps(question);
+    // No fee when the whole reward goes back to the asker
+    let fee = if refund_bps == 10_000 { 0 } else { available_reward * 2 / 100 };
+    let remaining = available_reward.saturating_sub(fee);
+    let refund = remaining * refund_bps / 10_000;
+    (fee, remaining - refund, refund)
+}
+
+/// Takes the 2% fee and splits the rest of the reward between the voters'
+/// pool and the asker's refund. Runs once, on the first claim of either.
+fn take_reward_snapshot<'info>(
+    question: &mut Question,
+    vault: &AccountInfo<'info>,
+    fee_receiver: &AccountInfo<'info>,
+) -> Result<()> {
+    let min_balance = Rent::get()?.minimum_balance(vault.data_len());
+
+    // Stake still owed back to revealers is not part of the reward
+    let available_reward = vault
+        .lamports()
+        .saturating_sub(min_balance)
+        .saturating_sub(outstanding_stake(question));
+    let (fee, pool, refund) = split_reward(question, available_reward);
+
+    transfer_from_vault(vault, fee_receiver, fee)?;
+
+    question.original_reward = available_reward;
+    question.snapshot_reward = pool;
+    question.asker_refund = refund;
+
+    question.snapshot_total_weight = rewarded_weight(question);
+
+    question.claimed_weight = 0;
+    question.claimed_voters_count = 0;
+    question.claimed_remainder_count = 0;
+    question.total_distributed = 0;
+    question.reward_fee_taken = true;
+
+    msg!(
+        "Reward snapshot initialized. Total weight: {}. Asker refund: {}",
+        question.snapshot_total_weight,
+        refund
+    );
+
+    Ok(())
+}
+
+/// Stake returned to a voter once the question is resolved. Unrevealed
+/// votes forfeit their stake.
+fn stake_refund(question: &Question, voter_record: &VoterRecord) -> u64 {
+    if !voter_record.revealed {
+        0
+    } else if is_rewarded_vote(question, voter_record) || !slashes_losers(question) {
+        voter_record.stake
+    } else {
+        voter_record.stake.saturating_sub(slash_per_vote(question))
+    }
+}
+
+/// Part of an operator's reward owed to their delegators: pro rata to the
+/// delegated share of their stake, after the operator's commission.
+fn delegator_reward_share(operator_record: &UserRecord, reward: u64, now: i64) -> u64 {
+    let delegated = operator_record.delegated_stake as u128;
+    if delegated == 0 {
+        return 0;
+    }
+    let total_stake = operator_record.stake as u128 + delegated;
+    let pro_rata = reward as u128 * delegated / total_stake;
+    (pro_rata * (10_000 - commission_at(operator_record, now) as u128) / 10_000) as u64
+}
+
+/// Commission the operator charges at `now`, including a pending increase
+/// once it is due.
+fn commission_at(operator_record: &UserRecord, now: i64) -> u16 {
+    if operator_record.pending_commission_at != 0 && now >= operator_record.pending_commission_at {
+        operator_record.pending_commission_bps
+    } else {
+        operator_record.commission_bps
+    }
+}
+
+fn accrued_delegation_rewards(amount: u64, operator_record: &UserRecord) -> u64 {
+    (amount as u128 * operator_record.reward_per_delegated / DELEGATION_REWARD_PRECISION) as u64
+}
+
+fn settle_delegation_rewards(delegation: &mut Delegation, operator_record: &UserRecord) {
+    let accrued = accrued_delegation_rewards(delegation.amount, operator_record);
+    delegation.pending_rewards += accrued.saturating_sub(delegation.reward_debt);
+}
+
+fn start_undelegation(
+    delegation: &mut Delegation,
+    operator_record: &mut UserRecord,
+    amount: u64,
+) -> Result<()> {
+    settle_delegation_rewards(delegation, operator_record);
+
+    delegation.amount -= amount;
+    delegation.reward_debt = accrued_delegation_rewards(delegation.amount, operator_record);
+    delegation.undelegating_amount += amount;
+    delegation.undelegate_requested_at = Clock::get()?.unix_timestamp;
+
+    // Undelegating stake stops counting towards the operator's weight right away
+    operator_record.delegated_stake -= amount;
+
+    msg!(
+        "{} started undelegating {} lamports from {}",
+        delegation.delegator,
+        amount,
+        delegation.operator
+    );
+    Ok(())
+}
+
+fn check_can_reveal(
+    question: &Question,
+    voter_record: &VoterRecord,
+    user_record: &UserRecord,
+) -> Result<()> {
+    require!(
+        user_record.created_at <= voter_record.user_record_join_time,
+        VotingError::RejoinedAfterCommit
+    );
+    require!(!voter_record.revealed, VotingError::AlreadyRevealed);
+    require!(Clock::get()?.unix_timestamp < question.reveal_end_time, VotingError::RevealPhaseEnded);
+    Ok(())
+}
+
+fn reveal_with_salt(
+    question_key: &Pubkey,
+    question: &mut Question,
+    voter_record: &mut VoterRecord,
+    user_record: &mut UserRecord,
+    option: u8,
+    salt: &[u8; 32],
+) -> Result<()> {
+    verify_salted_reveal(question_key, question, voter_record, user_record, option, salt)?;
+    record_reveal(question, voter_record, user_record, option)
+}
+
+fn verify_salted_reveal(
+    question_key: &Pubkey,
+    question: &Question,
+    voter_record: &VoterRecord,
+    user_record: &UserRecord,
+    option: u8,
+    salt: &[u8; 32],
+) -> Result<()> {
+    check_can_reveal(question, voter_record, user_record)?;
+    require!(
+        question.question_type != QUESTION_TYPE_SCALAR,
+        VotingError::WrongQuestionType
+    );
+    require!(is_valid_option(question, option), VotingError::InvalidOption);
+
+    let computed_hash = compute_commitment(
+        question.commitment_version,
+        &voter_record.voter,
+        question_key,
+        option,
+        salt,
+    );
+    require!(computed_hash == voter_record.commitment, VotingError::InvalidReveal);
+    Ok(())
+}
+
+fn record_reveal(
+    question: &mut Question,
+    voter_record: &mut VoterRecord,
+    user_record: &mut UserRecord,
+    vote: u8,
+) -> Result<()> {
+    let weight = vote_weight(question, user_record);
+    tally_reveal(question, voter_record, vote, weight);
+
+    msg!("Vote Revealed Successfully! Option {}", vote);
+    credit_reveals(user_record, 1);
+
+    Ok(())
+}
+
+/// Provider bookkeeping shared by every reveal path.
+fn credit_reveals(user_record: &mut UserRecord, revealed: u64) {
+    // Update user revealed votes
+    user_record.open_commitments = user_record.open_commitments.saturating_sub(revealed);
+    user_record.total_revealed_votes += revealed;
+
+    // Recalculate reputation based on new revealed/correct votes
+    user_record.reputation = calculate_reputation(
+        user_record.total_revealed_votes,
+        user_record.total_correct_votes,
+    );
+
+    msg!("New reputation: {}", user_record.reputation);
+}
+
+fn vote_weight(question: &Question, user_record: &UserRecord) -> u64 {
+    let reputation = if user_record.reputation == 0 { 1 } else { user_record.reputation as u64 };
+
+    match question.vote_weight_mode {
+        VOTE_WEIGHT_REPUTATION_LOG_STAKE => {
+            let staked_sol = (user_record.stake + user_record.delegated_stake) / LAMPORTS_PER_SOL;
+            reputation * (1 + (staked_sol + 1).ilog2() as u64)
+        }
+        _ => reputation,
+    }
+}
+
+fn tally_reveal(question: &mut Question, voter_record: &mut VoterRecord, vote: u8, vote_weight: u64) {
+    voter_record.revealed = true;
+    voter_record.selected_option = vote;
+    voter_record.vote_weight = vote_weight;
+
+    if vote == OPTION_INVALID {
+        question.invalid_votes += voter_record.vote_weight;
+        question.invalid_stake += voter_record.stake;
+    } else {
+        let index = vote as usize - 1;
+        question.votes[index] += voter_record.vote_weight;
+        question.revealed_stake[index] += voter_record.stake;
+    }
+
+    // Increment revealed count
+    question.revealed_voters_count += 1;
+}
+
+/// Reveals one `reveal_votes_batch` entry against its `(question, voter_record, vault)`
+/// account triple. Nothing is written back unless every check passes.
+fn reveal_batch_entry<'info>(
+    program_id: &Pubkey,
+    voter: &AccountInfo<'info>,
+    user_record: &UserRecord,
+    entry: &RevealEntry,
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<()> {
+    let [question_info, voter_record_info, vault_info] = accounts else {
+        return err!(VotingError::InvalidBatchEntry);
+    };
+    require!(
+        question_info.is_writable && voter_record_info.is_writable && vault_info.is_writable,
+        VotingError::InvalidBatchEntry
+    );
+
+    let mut question: Account<Question> = Account::try_from(question_info)?;
+    let mut voter_record: Account<VoterRecord> = Account::try_from(voter_record_info)?;
+
+    require_keys_eq!(voter_record.voter, voter.key(), VotingError::NotEligible);
+    require_keys_eq!(voter_record.question, question.key(), VotingError::InvalidBatchEntry);
+    require_keys_eq!(vault_info.key(), question.vault_address, VotingError::InvalidVaultAccount);
+
+    verify_salted_reveal(
+        &question.key(),
+        &question,
+        &voter_record,
+        user_record,
+        entry.option,
+        &entry.salt,
+    )?;
+    let weight = vote_weight(&question, user_record);
+    tally_reveal(&mut question, &mut voter_record, entry.option, weight);
+    refund_bond(vault_info, voter, &voter_record)?;
+
+    question.exit(program_id)?;
+    voter_record.exit(program_id)?;
+    Ok(())
+}
+
+/// Grows a program account allocated under an older layout to `new_len`,
+/// zeroing the new bytes. The payer covers the extra rent.
+fn grow_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let top_up = Rent::get()?
+        .minimum_balance(new_len)
+        .saturating_sub(account.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, top_up),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+    account.realloc(new_len, true)?;
+    Ok(())
+}
+
+fn transfer_from_vault<'info>(
+    vault: &AccountInfo<'info>,
+    recipient: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount > 0 {
+        **vault.try_borrow_mut_lamports()? -= amount;
+        **recipient.try_borrow_mut_lamports()? += amount;
+    }
+    Ok(())
+}
+
+fn refund_bond<'info>(
+    vault: &AccountInfo<'info>,
+    voter: &AccountInfo<'info>,
+    voter_record: &VoterRecord,
+) -> Result<()> {
+    let bond = voter_record.bond;
+    if bond > 0 {
+        **vault.try_borrow_mut_lamports()? -= bond;
+        **voter.try_borrow_mut_lamports()? += bond;
+        msg!("Commit bond of {} lamports returned", bond);
+    }
+    Ok(())
+}
+
+fn batch_error_code(err: Error) -> u32 {
+    match ProgramError::from(err) {
+        ProgramError::Custom(code) => code,
+        _ => VotingError::InvalidBatchEntry.into(),
+    }
+}
+
+fn compute_scalar_commitment(voter: &Pubkey, question: &Pubkey, value: i64, salt: &[u8]) -> [u8; 32] {
+    hashv(&[COMMITMENT_DOMAIN, voter.as_ref(), question.as_ref(), &value.to_le_bytes(), salt]).0
+}
+
+fn compute_commitment(
+    version: u8,
+    voter: &Pubkey,
+    question: &Pubkey,
+    vote: u8,
+    secret: &[u8],
+) -> [u8; 32] {
+    if version == COMMITMENT_VERSION_LEGACY {
+        // Same bytes as the old `format!("{}{}", vote, password)`
+        let vote_digits = vote.to_string();
+        hashv(&[vote_digits.as_bytes(), secret]).0
+    } else {
+        hashv(&[COMMITMENT_DOMAIN, voter.as_ref(), question.as_ref(), &[vote], secret]).0
+    }
+}
+
+fn calculate_reputation(revealed: u64, correct: u64) -> u8 {
+    let sum = revealed + correct;
+
+    match sum {
+        0 | 1 => 1,
+        2 => 2,
+        3 => 3,
+        4 => 4,
+        5 => 5,
+        6 => 6,
+        7..=9 => 7,
+        10..=12 => 8,
+        13..=17 => 9,
+        18..=25 => 10,
+        26..=34 => 11,
+        35..=46 => 12,
+        47..=62 => 13,
+        63..=87 => 14,
+        88..=118 => 15,
+        119..=163 => 16,
+        164..=224 => 17,
+        225..=308 => 18,
+        _ => 19,
+    }
+}
+
+
+#[account]
+#[derive(Default)]
+pub struct Question {
+    pub id: u64,
+    pub asker: Pubkey,
+    pub question_key: Pubkey,
+    pub vault_address: Pubkey,
+    pub question_text: String,
+    // Option labels; votes are cast for 1..=options.len()
+    pub options: Vec<String>,
+    pub created_at: i64,
+    pub commit_end_time: i64,
+    pub reveal_end_time: i64,
+    pub votes: [u64; MAX_OPTIONS],
+    pub finalized: bool,
+    pub committed_voters: u64,
+    pub revealed_voters_count: u64,
+    pub eligible_voters: u64,
+    pub winning_option: u8,
+    pub winning_percent: f64,
+    pub reward_fee_taken: bool,
+    pub snapshot_reward: u64,
+    pub original_reward: u64,
+    pub claimed_remainder_count: u64,
+    pub snapshot_total_weight: u64,
+    pub total_distributed: u64,
+    pub claimed_voters_count: u64,
+    pub claimed_weight: u64,
+    pub voter_records_count: u64,
+    pub voter_records_closed: u64,
+    pub reward_drained: bool,
+    pub action_in_progress: bool,
+    pub bump: u8,
+    pub commitment_version: u8,
+    pub commit_bond: u64,
+    pub min_membership_age: i64,
+    pub vote_stake: u64,
+    pub slash_rate_bps: u16,
+    pub revealed_stake: [u64; MAX_OPTIONS],
+    pub stake_returned: u64,
+    pub vote_weight_mode: u8,
+    pub winning_rule: u8,
+    pub question_type: u8,
+    // Scalar questions: accepted range, display decimals, tolerance around the
+    // weighted median that still earns the reward, and the reveals so far
+    pub scalar_min: i64,
+    pub scalar_max: i64,
+    pub scalar_decimals: u8,
+    pub scalar_tolerance: u64,
+    pub scalar_median: i64,
+    pub scalar_reveals: Vec<ScalarReveal>,
+    // Weight and stake revealed for OPTION_INVALID
+    pub invalid_votes: u64,
+    pub invalid_stake: u64,
+    // Part of the reward owed back to the asker, set with the reward snapshot
+    pub asker_refund: u64,
+    pub asker_refund_claimed: bool,
+    pub resolution_criteria_hash: Option<[u8; 32]>,
+    pub resolution_criteria_uri: Option<String>,
+    pub source_uri: Option<String>,
+    pub event_time: Option<i64>,
+    // Set once a QuestionContent account is attached
+    pub has_content: bool,
+    // Quorum the reveals must reach, otherwise the question resolves to
+    // OUTCOME_NO_QUORUM
+    pub min_revealed_voters: u64,
+    pub min_revealed_weight: u64,
+    // Share of the revealed weight the winner needs, otherwise the question
+    // resolves to OUTCOME_CONTESTED (0 = no threshold)
+    pub required_majority_bps: u16,
+    // Conditional questions: the parent question (default when none) and the
+    // outcome it must resolve to, otherwise this question is OUTCOME_VOID
+    pub parent_question: Pubkey,
+    pub parent_outcome: u8,
+    // Extension policy: both deadlines move by extension_delta, at most
+    // max_extensions times, while committed_voters < extension_threshold
+    pub extension_delta: i64,
+    pub max_extensions: u8,
+    pub extension_threshold: u64,
+    pub extensions_used: u8,
+}
+
+/// Long-form text for a question, written in chunks by the asker and locked
+/// before providers can commit.
+#[account]
+pub struct QuestionContent {
+    pub question: Pubkey,
+    pub locked: bool,
+    pub content: Vec<u8>,
+}
+
+/// `Question` as laid out before categorical questions; only read by
+/// `migrate_question`.
+#[derive(AnchorDeserialize)]
+pub struct LegacyQuestion {
+    pub id: u64,
+    pub asker: Pubkey,
+    pub question_key: Pubkey,
+    pub vault_address: Pubkey,
+    pub question_text: String,
+    pub option_1: String,
+    pub option_2: String,
+    pub created_at: i64,
+    pub commit_end_time: i64,
+    pub reveal_end_time: i64,
+    pub votes_option_1: u64,
+    pub votes_option_2: u64,
+    pub finalized: bool,
+    pub committed_voters: u64,
+    pub revealed_voters_count: u64,
+    pub eligible_voters: u64,
+    pub winning_option: u8,
+    pub winning_percent: f64,
+    pub reward_fee_taken: bool,
+    pub snapshot_reward: u64,
+    pub original_reward: u64,
+    pub claimed_remainder_count: u64,
+    pub snapshot_total_weight: u64,
+    pub total_distributed: u64,
+    pub claimed_voters_count: u64,
+    pub claimed_weight: u64,
+    pub voter_records_count: u64,
+    pub voter_records_closed: u64,
+    pub reward_drained: bool,
+    pub action_in_progress: bool,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ScalarReveal {
+    pub value: i64,
+    pub weight: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ScalarRange {
+    pub min_value: i64,
+    pub max_value: i64,
+    // Values are integers scaled by 10^decimals
+    pub decimals: u8,
+    pub tolerance: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct QuestionSettings {
+    // Lamports each provider posts at commit; returned on reveal, forfeited to
+    // the winners' pool otherwise
+    pub commit_bond: u64,
+    // Seconds providers must have joined before the question was created
+    // (defaults to NetworkConfig.default_min_membership_age)
+    pub min_membership_age: Option<i64>,
+    // Lamports each provider stakes at commit; returned at settlement, minus
+    // slash_rate_bps for revealers who voted against the winning option
+    pub vote_stake: u64,
+    pub slash_rate_bps: u16,
+    // VOTE_WEIGHT_REPUTATION or VOTE_WEIGHT_REPUTATION_LOG_STAKE
+    pub vote_weight_mode: u8,
+    // WINNING_RULE_PLURALITY or WINNING_RULE_MAJORITY
+    pub winning_rule: u8,
+    // Hash of the document describing how the question will be judged, and
+    // where to find it; the URI requires the hash
+    pub resolution_criteria_hash: Option<[u8; 32]>,
+    pub resolution_criteria_uri: Option<String>,
+    // Where providers should look up the answer
+    pub source_uri: Option<String>,
+    // When the event being asked about takes place
+    pub event_time: Option<i64>,
+    // Quorum: fewest revealed voters and least revealed weight needed for a
+    // result (0 = no minimum)
+    pub min_revealed_voters: u64,
+    pub min_revealed_weight: u64,
+    // Winner's minimum share of the revealed weight in basis points; below it
+    // the question is contested (0 = no threshold)
+    pub required_majority_bps: u16,
+    // Makes this a conditional question: commits open once the parent question
+    // passed to create_question resolves to this outcome
+    pub parent_outcome: Option<u8>,
+    // Seconds both deadlines move when the commit phase ends with fewer than
+    // extension_threshold commits, up to max_extensions times (0 = never)
+    pub extension_delta: i64,
+    pub max_extensions: u8,
+    pub extension_threshold: u64,
+}
+
+#[event]
+pub struct QuestionUpdated {
+    pub question: Pubkey,
+    pub question_text: String,
+    pub commit_end_time: i64,
+    pub reveal_end_time: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct WinnerResult {
+    pub total_votes: u64,
+    pub votes_option1: u64,
+    pub votes_option2: u64,
+    pub winning_option: u8,
+    pub winning_percent: f64,
+}
+
+
+#[derive(Accounts)]
+#[instruction(question_text: String)]
+pub struct CreateQuestion<'info> {
+    #[account(
+        mut,
+        seeds = [b"question_counter", asker.key().as_ref()],
+        bump,
+        has_one = asker
+    )]
+    pub question_counter: Account<'info, QuestionCounter>,
+
+    #[account(
+        seeds = [b"network_config"],
+        bump
+    )]
+    pub network_config: Account<'info, NetworkConfig>,
+
+    #[account(
+        init,
+        payer = asker,
+        space = question_space(&question_text),
+        seeds = [b"question", asker.key().as_ref(), &question_counter.count.to_le_bytes()],
+        bump
+    )]
+    pub question: Account<'info, Question>, 
+
+    // Change vault from UncheckedAccount to Account<Vault>
+    #[account(
+        init,
+        payer = asker,
+        space = 8,  // Minimal space for Vault (only the 8-byte discriminator).
+        seeds = [b"vault", question.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub asker: Signer<'info>,
+
+    // Required with QuestionSettings.parent_outcome
+    pub parent_question: Option<Account<'info, Question>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct DrainUnclaimedReward<'info> {
+    #[account(
+        mut,
+        seeds = [b"question", question.asker.as_ref(), &question.id.to_le_bytes()],
+        bump = question.bump
+    )]
+    pub question: Account<'info, Question>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", question.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// CHECK: Constant public key
+    #[account(mut, address = FEE_RECEIVER_PUBKEY)]
+    pub fee_receiver: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+
+
+// #[derive(Accounts)]
+// pub struct UpdateReward<'info> {
+//     #[account(mut)]
+//     pub question: Account<'info, Question>,
+//     /// CHECK: This account is not required to sign; its authorization is managed by the caller.
+//     pub updater: UncheckedAccount<'info>,
+// }
+
+
+#[derive(Accounts)]
+pub struct DeleteExpiredQuestion<'info> {
+    #[account(
+        mut,
+        seeds = [b"question", asker.key().as_ref(), &question.id.to_le_bytes()],
+        bump,
+        close = asker
+    )]
+    pub question: Account<'info, Question>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", question.key().as_ref()],
+        bump,
+        close = asker
+    )]
+    /// CHECK: This is a PDA with no data except discriminator, verified via seeds and bump
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"question_content", question.key().as_ref()],
+        bump,
+        close = asker
+    )]
+    pub question_content: Option<Account<'info, QuestionContent>>,
+
+    #[account(mut)]
+    pub asker: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ExtendQuestion<'info> {
+    #[account(
+        mut,
+        seeds = [b"question", question.asker.as_ref(), &question.id.to_le_bytes()],
+        bump = question.bump
+    )]
+    pub question: Account<'info, Question>,
+}
+
+#[derive(Accounts)]
+#[instruction(question_text: String)]
+pub struct UpdateQuestion<'info> {
+    #[account(
+        mut,
+        seeds = [b"question", asker.key().as_ref(), &question.id.to_le_bytes()],
+        bump = question.bump,
+        realloc = question_space(&question_text),
+        realloc::payer = asker,
+        realloc::zero = false
+    )]
+    pub question: Account<'info, Question>,
+
+    #[account(mut)]
+    pub asker: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelQuestion<'info> {
+    #[account(
+        mut,
+        seeds = [b"question", asker.key().as_ref(), &question.id.to_le_bytes()],
+        bump = question.bump,
+        close = asker
+    )]
+    pub question: Account<'info, Question>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", question.key().as_ref()],
+        bump,
+        close = asker
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(
+        mut,
+        seeds = [b"question_content", question.key().as_ref()],
+        bump,
+        close = asker
+    )]
+    pub question_content: Option<Account<'info, QuestionContent>>,
+
+    #[account(mut)]
+    pub asker: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(total_len: u32)]
+pub struct InitQuestionContent<'info> {
+    #[account(
+        mut,
+        seeds = [b"question", asker.key().as_ref(), &question.id.to_le_bytes()],
+        bump = question.bump
+    )]
+    pub question: Account<'info, Question>,
+
+    #[account(
+        init,
+        payer = asker,
+        space = 8 + 32 + 1 + 4 + total_len as usize,
+        seeds = [b"question_content", question.key().as_ref()],
+        bump
+    )]
+    pub question_content: Account<'info, QuestionContent>,
+
+    #[account(mut)]
+    pub asker: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WriteQuestionContent<'info> {
+    #[account(
+        seeds = [b"question", asker.key().as_ref(), &question.id.to_le_bytes()],
+        bump = question.bump
+    )]
+    pub question: Account<'info, Question>,
+
+    #[account(
+        mut,
+        seeds = [b"question_content", question.key().as_ref()],
+        bump
+    )]
+    pub question_content: Account<'info, QuestionContent>,
+
+    pub asker: Signer<'info>,
+}
+
+
+
+#[derive(Accounts)]
+pub struct JoinNetwork<'info> {
+    #[account(
+        mut,
+        seeds = [b"global_state"],
+        bump
+    )]
+    pub global_state: Account<'info, GlobalState>,
+
+    #[account(
+        seeds = [b"network_config"],
+        bump
+    )]
+    pub network_config: Account<'info, NetworkConfig>,
+
+    #[account(
+        init,
+        payer = user,
+        space = USER_RECORD_SPACE,
+        seeds = [b"user_record", user.key().as_ref()],
+        bump
+    )]
+    pub user_record: Account<'info, UserRecord>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, StakeVault>,
+
+    /// CHECK: Invite is optional; validate manually if present
+    pub invite: Option<AccountInfo<'info>>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+
+
+
+#[derive(Accounts)]
+pub struct AddStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_record", user.key().as_ref()],
+        bump
+    )]
+    pub user_record: Account<'info, UserRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, StakeVault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetCommission<'info> {
+    #[account(
+        seeds = [b"network_config"],
+        bump
+    )]
+    pub network_config: Account<'info, NetworkConfig>,
+
+    #[account(
+        mut,
+        seeds = [b"user_record", user.key().as_ref()],
+        bump
+    )]
+    pub user_record: Account<'info, UserRecord>,
+
+    pub user: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct DelegateStake<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_record", operator.key().as_ref()],
+        bump
+    )]
+    pub operator_record: Account<'info, UserRecord>,
+
+    #[account(
+        init_if_needed,
+        payer = delegator,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8, // discriminator + delegator + operator + amount + reward_debt + pending_rewards + undelegating_amount + undelegate_requested_at
+        seeds = [b"delegation", delegator.key().as_ref(), operator.key().as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
+
+    #[account(
+        init_if_needed,
+        payer = delegator,
+        space = 8,
+        seeds = [b"delegation_pool", operator.key().as_ref()],
+        bump
+    )]
+    pub delegation_pool: Account<'info, DelegationPool>,
+
+    /// CHECK: Only used to derive the operator's PDAs.
+    pub operator: UncheckedAccount<'info>,
 
     #[account(mut)]
-    pub asker: Signer<'info>,
+    pub delegator: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct DrainUnclaimedReward<'info> {
+pub struct ClaimDelegationRewards<'info> {
+    #[account(
+        seeds = [b"user_record", operator.key().as_ref()],
+        bump
+    )]
+    pub operator_record: Account<'info, UserRecord>,
+
     #[account(
         mut,
-        seeds = [b"question", question.asker.as_ref(), &question.id.to_le_bytes()],
-        bump = question.bump
+        seeds = [b"delegation", delegator.key().as_ref(), operator.key().as_ref()],
+        bump
     )]
-    pub question: Account<'info, Question>,
+    pub delegation: Account<'info, Delegation>,
 
     #[account(
         mut,
-        seeds = [b"vault", question.key().as_ref()],
+        seeds = [b"delegation_pool", operator.key().as_ref()],
         bump
     )]
-    pub vault: Account<'info, Vault>,
+    pub delegation_pool: Account<'info, DelegationPool>,
 
-    /// CHECK: Constant public key
-    #[account(mut, address = FEE_RECEIVER_PUBKEY)]
-    pub fee_receiver: AccountInfo<'info>,
+    /// CHECK: Only used to derive the operator's PDAs.
+    pub operator: UncheckedAccount<'info>,
 
-    pub system_program: Program<'info, System>,
+    #[account(mut)]
+    pub delegator: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct RequestUndelegate<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_record", operator.key().as_ref()],
+        bump
+    )]
+    pub operator_record: Account<'info, UserRecord>,
 
+    #[account(
+        mut,
+        seeds = [b"delegation", delegator.key().as_ref(), operator.key().as_ref()],
+        bump
+    )]
+    pub delegation: Account<'info, Delegation>,
 
-// #[derive(Accounts)]
-// pub struct UpdateReward<'info> {
-//     #[account(mut)]
-//     pub question: Account<'info, Question>,
-//     /// CHECK: This account is not required to sign; its authorization is managed by the caller.
-//     pub updater: UncheckedAccount<'info>,
-// }
+    /// CHECK: Only used to derive the operator's PDAs.
+    pub operator: UncheckedAccount<'info>,
 
+    pub delegator: Signer<'info>,
+}
 
 #[derive(Accounts)]
-pub struct DeleteExpiredQuestion<'info> {
+pub struct ForceUndelegate<'info> {
     #[account(
         mut,
-        seeds = [b"question", asker.key().as_ref(), &question.id.to_le_bytes()],
-        bump,
-        close = asker
+        seeds = [b"user_record", operator.key().as_ref()],
+        bump
     )]
-    pub question: Account<'info, Question>,
+    pub operator_record: Account<'info, UserRecord>,
 
     #[account(
         mut,
-        seeds = [b"vault", question.key().as_ref()],
-        bump,
-        close = asker
+        seeds = [b"delegation", delegator.key().as_ref(), operator.key().as_ref()],
+        bump
     )]
-    /// CHECK: This is a PDA with no data except discriminator, verified via seeds and bump
-    pub vault: Account<'info, Vault>,
+    pub delegation: Account<'info, Delegation>,
 
-    #[account(mut)]
-    pub asker: Signer<'info>,
+    /// CHECK: Only used to derive the delegation PDA.
+    pub delegator: UncheckedAccount<'info>,
 
-    pub system_program: Program<'info, System>,
+    pub operator: Signer<'info>,
 }
 
-
-
 #[derive(Accounts)]
-pub struct JoinNetwork<'info> {
+pub struct CompleteUndelegate<'info> {
+    #[account(
+        seeds = [b"network_config"],
+        bump
+    )]
+    pub network_config: Account<'info, NetworkConfig>,
+
     #[account(
         mut,
-        seeds = [b"global_state"],
+        seeds = [b"delegation", delegator.key().as_ref(), operator.key().as_ref()],
         bump
     )]
-    pub global_state: Account<'info, GlobalState>,
+    pub delegation: Account<'info, Delegation>,
 
     #[account(
-        init,
-        payer = user,
-        space = 8 + 80,
-        seeds = [b"user_record", user.key().as_ref()],
+        mut,
+        seeds = [b"delegation_pool", operator.key().as_ref()],
         bump
     )]
-    pub user_record: Account<'info, UserRecord>,
+    pub delegation_pool: Account<'info, DelegationPool>,
 
-    /// CHECK: Invite is optional; validate manually if present
-    pub invite: Option<AccountInfo<'info>>,
+    /// CHECK: Only used to derive the operator's PDAs; the operator may have left.
+    pub operator: UncheckedAccount<'info>,
 
     #[account(mut)]
-    pub user: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
+    pub delegator: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct RequestLeave<'info> {
+    #[account(
+        seeds = [b"network_config"],
+        bump
+    )]
+    pub network_config: Account<'info, NetworkConfig>,
 
+    #[account(
+        mut,
+        seeds = [b"user_record", user.key().as_ref()],
+        bump
+    )]
+    pub user_record: Account<'info, UserRecord>,
 
+    pub user: Signer<'info>,
+}
 
 #[derive(Accounts)]
-pub struct LeaveNetwork<'info> {
-    
+pub struct CompleteLeave<'info> {
+    #[account(
+        seeds = [b"network_config"],
+        bump
+    )]
+    pub network_config: Account<'info, NetworkConfig>,
+
     #[account(
         mut,
         seeds = [b"user_record", user.key().as_ref()],
@@ -872,6 +3089,14 @@ pub struct LeaveNetwork<'info> {
     )]
     pub user_record: Account<'info, UserRecord>,
 
+    #[account(
+        mut,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump,
+        close = user
+    )]
+    pub stake_vault: Account<'info, StakeVault>,
+
     #[account(mut, signer)]
     pub user: Signer<'info>,
 
@@ -903,87 +3128,289 @@ pub struct QuestionCounter {
     pub count: u64,
 }
 
-#[derive(Accounts)]
-pub struct CreateVoterRecord<'info> {
-    #[account(mut)]
-    pub question: Account<'info, Question>,
+#[derive(Accounts)]
+pub struct CreateVoterRecord<'info> {
+    #[account(mut)]
+    pub question: Account<'info, Question>,
+
+    #[account(
+        init_if_needed,
+        payer = voter, 
+        space = VOTER_RECORD_SPACE,
+        seeds = [b"vote", voter.key().as_ref(), question.key().as_ref()],
+        bump
+    )]
+    pub voter_record: Account<'info, VoterRecord>, 
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[account]
+#[derive(Default)]
+pub struct UserRecord {
+   pub user: Pubkey,
+   pub reputation: u8,
+   pub total_earnings: u64,
+   pub total_revealed_votes: u64,
+   pub total_correct_votes: u64,
+   pub invite_correct_votes: u64,
+   pub invite_tokens: u8,
+   pub created_at: i64,
+   // Lamports held in the user's stake vault (deposit plus top-ups)
+   pub stake: u64,
+   // When the user started unbonding (0 = not leaving)
+   pub leave_requested_at: i64,
+   // Committed votes not yet revealed, withdrawn or reclaimed
+   pub open_commitments: u64,
+   // Stake delegated to this user as an operator
+   pub delegated_stake: u64,
+   // Share of delegators' rewards the operator keeps, in basis points
+   pub commission_bps: u16,
+   // Delegator rewards per delegated lamport, scaled by DELEGATION_REWARD_PRECISION
+   pub reward_per_delegated: u128,
+   // Commission increase waiting out the unbonding period
+   pub pending_commission_bps: u16,
+   // When pending_commission_bps takes effect (0 = nothing pending)
+   pub pending_commission_at: i64,
+}
+
+#[account]
+pub struct Delegation {
+    pub delegator: Pubkey,
+    pub operator: Pubkey,
+    pub amount: u64,
+    pub reward_debt: u64,
+    pub pending_rewards: u64,
+    pub undelegating_amount: u64,
+    pub undelegate_requested_at: i64,
+}
+
+
+#[account]
+pub struct VoterRecord {
+    pub question: Pubkey,
+    pub voter: Pubkey,
+    pub selected_option: u8,
+    pub commitment: [u8; 32],
+    pub revealed: bool,
+    pub claimed: bool,
+    pub claim_tx_id: [u8; 64],
+    pub vote_weight: u64,
+    pub user_record_join_time: i64,
+    // Optional key allowed to reveal on the voter's behalf (default = none)
+    pub revealer: Pubkey,
+    pub bond: u64,
+    pub stake: u64,
+    pub scalar_value: i64,
+}
+
+#[derive(Accounts)]
+pub struct CommitVote<'info> {
+    #[account(mut)]
+    pub question: Account<'info, Question>,
+
+    #[account(
+        init_if_needed,
+        payer = voter,
+        space = VOTER_RECORD_SPACE,
+        seeds = [b"vote", voter.key().as_ref(), question.key().as_ref()],
+        bump
+    )]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"user_record", voter.key().as_ref()],
+        bump
+    )]
+    pub user_record: Account<'info, UserRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", question.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    // Required when the question has long-form content
+    #[account(
+        seeds = [b"question_content", question.key().as_ref()],
+        bump
+    )]
+    pub question_content: Option<Account<'info, QuestionContent>>,
+
+    // Required for conditional questions
+    pub parent_question: Option<Account<'info, Question>>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateVoterRecord<'info> {
+    /// CHECK: Allocated under the old layout, so it cannot be loaded as a VoterRecord yet.
+    /// Checked by owner and discriminator.
+    #[account(mut, owner = crate::ID)]
+    pub voter_record: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateQuestion<'info> {
+    /// CHECK: Allocated under the old layout, so it cannot be loaded as a Question yet.
+    /// Checked by owner, discriminator and seeds.
+    #[account(mut, owner = crate::ID)]
+    pub question: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateNetworkConfig<'info> {
+    /// CHECK: Allocated under the old layout, so it cannot be loaded as a NetworkConfig yet.
+    /// Checked by seeds, owner and discriminator.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"network_config"],
+        bump
+    )]
+    pub network_config: UncheckedAccount<'info>,
+
+    #[account(mut, address = NETWORK_AUTHORITY)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct MigrateUserRecord<'info> {
+    /// CHECK: Allocated under the old layout, so it cannot be loaded as a UserRecord yet.
+    /// Checked by seeds, owner and discriminator.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"user_record", user.key().as_ref()],
+        bump
+    )]
+    pub user_record: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = user,
+        space = 8,
+        seeds = [b"stake_vault", user.key().as_ref()],
+        bump
+    )]
+    pub stake_vault: Account<'info, StakeVault>,
+
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct RecommitVote<'info> {
+    pub question: Account<'info, Question>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", voter.key().as_ref(), question.key().as_ref()],
+        bump
+    )]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    pub voter: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawCommitment<'info> {
+    #[account(
+        mut,
+        seeds = [b"question", question.asker.as_ref(), &question.id.to_le_bytes()],
+        bump = question.bump
+    )]
+    pub question: Account<'info, Question>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", voter.key().as_ref(), question.key().as_ref()],
+        bump,
+        close = voter
+    )]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    #[account(
+        mut,
+        seeds = [b"user_record", voter.key().as_ref()],
+        bump
+    )]
+    pub user_record: Account<'info, UserRecord>,
 
     #[account(
-        init_if_needed,
-        payer = voter, 
-        space = 8 + 200,
-        seeds = [b"vote", voter.key().as_ref(), question.key().as_ref()],
+        mut,
+        seeds = [b"vault", question.key().as_ref()],
         bump
     )]
-    pub voter_record: Account<'info, VoterRecord>, 
+    pub vault: Account<'info, Vault>,
 
     #[account(mut)]
     pub voter: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-#[account]
-pub struct UserRecord {
-   pub user: Pubkey,
-   pub reputation: u8,
-   pub total_earnings: u64,
-   pub total_revealed_votes: u64,
-   pub total_correct_votes: u64,
-   pub invite_correct_votes: u64,
-   pub invite_tokens: u8,
-   pub created_at: i64,
-}
-
-
-#[account]
-pub struct VoterRecord {
-    pub question: Pubkey,
-    pub voter: Pubkey,
-    pub selected_option: u8,
-    pub commitment: [u8; 32],
-    pub revealed: bool,
-    pub claimed: bool,
-    pub claim_tx_id: [u8; 64],
-    pub vote_weight: u64,
-    pub user_record_join_time: i64,
 }
 
 #[derive(Accounts)]
-pub struct CommitVote<'info> {
+pub struct RevealVote<'info> {
     #[account(mut)]
     pub question: Account<'info, Question>,
 
     #[account(
-        init_if_needed,
-        payer = voter,
-        space = 8 + 200,
+        mut,
         seeds = [b"vote", voter.key().as_ref(), question.key().as_ref()],
         bump
     )]
     pub voter_record: Account<'info, VoterRecord>,
 
     #[account(
+        mut,
         seeds = [b"user_record", voter.key().as_ref()],
         bump
     )]
     pub user_record: Account<'info, UserRecord>,
 
+    #[account(
+        mut,
+        seeds = [b"vault", question.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
     #[account(mut)]
     pub voter: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-pub struct RevealVote<'info> {
+pub struct RevealVoteDelegated<'info> {
     #[account(mut)]
     pub question: Account<'info, Question>,
 
     #[account(
         mut,
         seeds = [b"vote", voter.key().as_ref(), question.key().as_ref()],
-        bump
+        bump,
+        constraint = voter_record.revealer == revealer.key() @ VotingError::NotRevealer
     )]
     pub voter_record: Account<'info, VoterRecord>,
 
@@ -994,6 +3421,36 @@ pub struct RevealVote<'info> {
     )]
     pub user_record: Account<'info, UserRecord>,
 
+    #[account(
+        mut,
+        seeds = [b"vault", question.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// CHECK: Used to derive the voter's PDAs and receives the returned bond;
+    /// the registered revealer signs instead.
+    #[account(mut)]
+    pub voter: UncheckedAccount<'info>,
+
+    pub revealer: Signer<'info>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RevealEntry {
+    pub option: u8,
+    pub salt: [u8; 32],
+}
+
+#[derive(Accounts)]
+pub struct RevealVotesBatch<'info> {
+    #[account(
+        mut,
+        seeds = [b"user_record", voter.key().as_ref()],
+        bump
+    )]
+    pub user_record: Account<'info, UserRecord>,
+
     #[account(mut)]
     pub voter: Signer<'info>,
 }
@@ -1002,6 +3459,9 @@ pub struct RevealVote<'info> {
 pub struct FinalizeVoting<'info> {
     #[account(mut)]
     pub question: Account<'info, Question>,
+
+    // Required for conditional questions
+    pub parent_question: Option<Account<'info, Question>>,
 }
 
 
@@ -1031,17 +3491,79 @@ pub struct ClaimReward<'info> {
     )]
     pub user_record: Account<'info, UserRecord>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"vault", question.key().as_ref()],
+        bump
+    )]
     pub vault: Account<'info, Vault>,
     /// CHECK: This is a fixed known address for the fee receiver, no need for ownership verification.
     #[account(mut, address = FEE_RECEIVER_PUBKEY)]
     pub fee_receiver: AccountInfo<'info>,
 
+    // Required when the voter has delegated stake and the question weighs votes by stake
+    #[account(
+        mut,
+        seeds = [b"delegation_pool", voter.key().as_ref()],
+        bump
+    )]
+    pub delegation_pool: Option<Account<'info, DelegationPool>>,
+
     pub system_program: Program<'info, System>,
 }
 
 
 
+#[derive(Accounts)]
+pub struct ClaimAskerRefund<'info> {
+    #[account(
+        mut,
+        seeds = [b"question", asker.key().as_ref(), &question.id.to_le_bytes()],
+        bump = question.bump
+    )]
+    pub question: Account<'info, Question>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", question.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    #[account(mut)]
+    pub asker: Signer<'info>,
+
+    /// CHECK: This is a fixed known address for the fee receiver, no need for ownership verification.
+    #[account(mut, address = FEE_RECEIVER_PUBKEY)]
+    pub fee_receiver: AccountInfo<'info>,
+}
+
+#[derive(Accounts)]
+pub struct ReleaseOrphanedCommitment<'info> {
+    #[account(
+        mut,
+        seeds = [b"vote", voter.key().as_ref(), question.key().as_ref()],
+        bump,
+        has_one = voter,
+        close = voter
+    )]
+    pub voter_record: Account<'info, VoterRecord>,
+
+    /// CHECK: The record's question; must have been closed.
+    #[account(address = voter_record.question)]
+    pub question: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"user_record", voter.key().as_ref()],
+        bump
+    )]
+    pub user_record: Account<'info, UserRecord>,
+
+    #[account(mut)]
+    pub voter: Signer<'info>,
+}
+
 #[derive(Accounts)]
 pub struct ReclaimCommitOrLoserRent<'info> {
     #[account(
@@ -1061,6 +3583,22 @@ pub struct ReclaimCommitOrLoserRent<'info> {
         bump = question.bump
     )]
     pub question: Account<'info, Question>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", question.key().as_ref()],
+        bump
+    )]
+    pub vault: Account<'info, Vault>,
+
+    /// CHECK: Closed once the user has left, so it may not exist.
+    /// Checked by seeds, and by owner and discriminator before it is updated.
+    #[account(
+        mut,
+        seeds = [b"user_record", voter.key().as_ref()],
+        bump
+    )]
+    pub user_record: UncheckedAccount<'info>,
 }
 
 #[derive(Accounts)]
@@ -1130,6 +3668,32 @@ pub struct InitializeGlobalState<'info> {
     pub system_program: Program<'info, System>,
 }
 
+#[account]
+pub struct NetworkConfig {
+    pub provider_deposit: u64,
+    // Seconds between request_leave and complete_leave
+    pub unbonding_period: i64,
+    // Membership age required by questions that do not set their own
+    pub default_min_membership_age: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetNetworkConfig<'info> {
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = NETWORK_CONFIG_SPACE,
+        seeds = [b"network_config"],
+        bump
+    )]
+    pub network_config: Account<'info, NetworkConfig>,
+
+    #[account(mut, address = NETWORK_AUTHORITY)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
 #[derive(Accounts)]
 pub struct DeleteInvite<'info> {
     #[account(
@@ -1184,7 +3748,7 @@ pub enum VotingError {
     InsufficientFunds,
     #[msg("Overflow")]
     Overflow,
-    #[msg("Winning votes do not meet the required 51% majority.")]
+    #[msg("Winning votes do not meet the required majority.")]
     InsufficientMajority,
     #[msg("Question ID mismatch.")]
     QuestionIdMismatch,
@@ -1228,6 +3792,94 @@ pub enum VotingError {
     ActionInProgress,
     #[msg("Invalid invitee address.")]
     InvalidInvitee,
+    #[msg("Invalid option.")]
+    InvalidOption,
+    #[msg("This key is not authorized to reveal this vote.")]
+    NotRevealer,
+    #[msg("Batch entry does not match its accounts.")]
+    InvalidBatchEntry,
+    #[msg("Minimum membership age cannot be negative.")]
+    InvalidMembershipAge,
+    #[msg("You joined the network too recently to vote on this question.")]
+    MembershipTooRecent,
+    #[msg("You have requested to leave the network.")]
+    LeaveRequested,
+    #[msg("You have already requested to leave the network.")]
+    LeaveAlreadyRequested,
+    #[msg("You have not requested to leave the network.")]
+    LeaveNotRequested,
+    #[msg("Unbonding period is not over yet.")]
+    UnbondingNotOver,
+    #[msg("You still have unrevealed commitments.")]
+    OpenCommitmentsExist,
+    #[msg("Slash rate cannot exceed 100%.")]
+    InvalidSlashRate,
+    #[msg("Unknown vote weight mode.")]
+    InvalidVoteWeightMode,
+    #[msg("Commission cannot exceed 100%.")]
+    InvalidCommission,
+    #[msg("Stake is still delegated to you.")]
+    DelegationsOutstanding,
+    #[msg("The delegation pool account is required.")]
+    MissingDelegationPool,
+    #[msg("A question needs between 2 and 8 options.")]
+    InvalidOptionCount,
+    #[msg("Option labels must be between 1 and 32 characters long.")]
+    InvalidOptionLabel,
+    #[msg("Unknown winning rule.")]
+    InvalidWinningRule,
+    #[msg("Invalid scalar range.")]
+    InvalidScalarRange,
+    #[msg("Value is outside the question's range.")]
+    ScalarOutOfRange,
+    #[msg("This instruction does not apply to this type of question.")]
+    WrongQuestionType,
+    #[msg("This question has reached its maximum number of voters.")]
+    TooManyVoters,
+    #[msg("No refund is owed to the asker.")]
+    NoAskerRefund,
+    #[msg("URI is too long.")]
+    UriTooLong,
+    #[msg("A resolution criteria URI needs its content hash.")]
+    MissingCriteriaHash,
+    #[msg("Question content is too long.")]
+    ContentTooLong,
+    #[msg("Question content is locked.")]
+    ContentLocked,
+    #[msg("Question content must be locked before committing.")]
+    ContentNotLocked,
+    #[msg("Question content is not valid UTF-8.")]
+    InvalidContent,
+    #[msg("The question content account is required.")]
+    MissingQuestionContent,
+    #[msg("The question already has commitments.")]
+    QuestionHasCommitments,
+    #[msg("Required majority must be at most 10000 basis points.")]
+    InvalidMajorityThreshold,
+    #[msg("The parent question account is missing or does not match.")]
+    MissingParentQuestion,
+    #[msg("The parent question has not been finalized.")]
+    ParentNotFinalized,
+    #[msg("The parent question did not resolve to the required outcome.")]
+    ParentOutcomeMismatch,
+    #[msg("The question has not been finalized.")]
+    NotFinalized,
+    #[msg("Invalid extension policy.")]
+    InvalidExtensionPolicy,
+    #[msg("The question cannot be extended.")]
+    CannotExtend,
+    #[msg("The question can still be extended.")]
+    ExtensionAvailable,
+    #[msg("Account already uses the current layout.")]
+    AlreadyMigrated,
+    #[msg("The question still exists; reclaim through it instead.")]
+    QuestionStillExists,
+    #[msg("The parent question cannot resolve to this outcome.")]
+    InvalidParentOutcome,
+    #[msg("The commit phase must end after the parent's reveal phase.")]
+    CommitEndsBeforeParent,
+    #[msg("A parent question was passed without a parent outcome.")]
+    UnexpectedParentQuestion,
 }
 
 #[cfg(not(feature = "no-entrypoint"))]
@@ -1250,4 +3902,343 @@ auditors: "vtscc.org",
 acknowledgements: "Truth Network"
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAKE: u64 = 1_000_000;
+
+    fn categorical_question(option_count: usize) -> Question {
+        Question {
+            options: (1..=option_count).map(|option| format!("Option {}", option)).collect(),
+            winning_option: 255,
+            vote_stake: STAKE,
+            slash_rate_bps: 2_500,
+            ..Default::default()
+        }
+    }
+
+    fn revealed_vote(option: u8, weight: u64) -> VoterRecord {
+        VoterRecord {
+            question: Pubkey::default(),
+            voter: Pubkey::default(),
+            selected_option: option,
+            commitment: [1u8; 32],
+            revealed: true,
+            claimed: false,
+            claim_tx_id: [0u8; 64],
+            vote_weight: weight,
+            user_record_join_time: 0,
+            revealer: Pubkey::default(),
+            bond: 0,
+            stake: STAKE,
+            scalar_value: 0,
+        }
+    }
+
+    fn reveal_all(question: &mut Question, votes: &[(u8, u64)]) -> Vec<VoterRecord> {
+        votes
+            .iter()
+            .map(|&(option, weight)| {
+                let mut voter_record = revealed_vote(option, 0);
+                tally_reveal(question, &mut voter_record, option, weight);
+                voter_record
+            })
+            .collect()
+    }
+
+    /// Every lamport of revealed stake is either refunded or slashed into the pool.
+    fn assert_stake_conserved(question: &Question, voter_records: &[VoterRecord]) {
+        let refunded: u64 = voter_records
+            .iter()
+            .map(|voter_record| stake_refund(question, voter_record))
+            .sum();
+        assert_eq!(refunded + slashed_stake(question), revealed_stake_total(question));
+    }
+
+    #[test]
+    fn slashing_conserves_revealed_stake() {
+        let mut question = categorical_question(3);
+        let voter_records = reveal_all(
+            &mut question,
+            &[(1, 3), (1, 2), (2, 1), (3, 1), (OPTION_INVALID, 1)],
+        );
+        question.winning_option = tally_winner(&question).0;
+
+        assert_eq!(question.winning_option, 1);
+        assert_eq!(slashed_stake(&question), 3 * STAKE / 4);
+        assert_stake_conserved(&question, &voter_records);
+    }
+
+    #[test]
+    fn invalid_outcome_slashes_option_voters() {
+        let mut question = categorical_question(2);
+        let voter_records = reveal_all(&mut question, &[(OPTION_INVALID, 5), (1, 2), (2, 2)]);
+        question.winning_option = tally_winner(&question).0;
+
+        assert_eq!(question.winning_option, OPTION_INVALID);
+        assert_eq!(slashed_stake(&question), 2 * STAKE / 4);
+        assert_stake_conserved(&question, &voter_records);
+    }
+
+    #[test]
+    fn unresolved_outcomes_return_stake_in_full() {
+        for outcome in [0, OUTCOME_NO_QUORUM, OUTCOME_CONTESTED, OUTCOME_VOID] {
+            let mut question = categorical_question(2);
+            let voter_records = reveal_all(&mut question, &[(1, 2), (2, 2)]);
+            question.winning_option = outcome;
+
+            assert_eq!(slashed_stake(&question), 0);
+            for voter_record in &voter_records {
+                assert_eq!(stake_refund(&question, voter_record), STAKE);
+            }
+        }
+    }
+
+    #[test]
+    fn unrevealed_votes_forfeit_stake() {
+        let mut question = categorical_question(2);
+        let mut voter_records = reveal_all(&mut question, &[(1, 2), (2, 1)]);
+        question.winning_option = tally_winner(&question).0;
+
+        let mut unrevealed = revealed_vote(1, 0);
+        unrevealed.revealed = false;
+        assert_eq!(stake_refund(&question, &unrevealed), 0);
+
+        voter_records.push(unrevealed);
+        assert_stake_conserved(&question, &voter_records);
+    }
+
+    fn scalar_question(reveals: &[(i64, u64)], tolerance: u64) -> Question {
+        Question {
+            question_type: QUESTION_TYPE_SCALAR,
+            winning_option: 255,
+            scalar_tolerance: tolerance,
+            scalar_reveals: reveals
+                .iter()
+                .map(|&(value, weight)| ScalarReveal { value, weight })
+                .collect(),
+            revealed_voters_count: reveals.len() as u64,
+            ..Default::default()
+        }
+    }
+
+    fn reveals(values: &[(i64, u64)]) -> Vec<ScalarReveal> {
+        values.iter().map(|&(value, weight)| ScalarReveal { value, weight }).collect()
+    }
+
+    #[test]
+    fn weighted_median_needs_revealed_weight() {
+        assert_eq!(weighted_median(&[]), None);
+        assert_eq!(weighted_median(&reveals(&[(5, 0), (7, 0)])), None);
+        assert_eq!(weighted_median(&reveals(&[(-3, 1)])), Some(-3));
+    }
+
+    #[test]
+    fn weighted_median_follows_weight() {
+        assert_eq!(weighted_median(&reveals(&[(30, 1), (10, 1), (20, 1)])), Some(20));
+        assert_eq!(weighted_median(&reveals(&[(10, 1), (20, 1), (90, 5)])), Some(90));
+        assert_eq!(weighted_median(&reveals(&[(i64::MIN, 2), (i64::MAX, 1)])), Some(i64::MIN));
+    }
+
+    #[test]
+    fn weighted_median_takes_lower_value_on_even_split() {
+        assert_eq!(weighted_median(&reveals(&[(20, 1), (10, 1)])), Some(10));
+        assert_eq!(weighted_median(&reveals(&[(10, 2), (20, 1), (30, 1)])), Some(10));
+    }
+
+    #[test]
+    fn scalar_winner_reports_weight_within_tolerance() {
+        let question = scalar_question(&[(100, 2), (104, 1), (150, 1)], 5);
+        assert_eq!(tally_winner(&question), (1, 75.0));
+
+        let question = scalar_question(&[], 5);
+        assert_eq!(tally_winner(&question), (0, 0.0));
+    }
+
+    #[test]
+    fn tied_options_resolve_to_zero() {
+        let mut question = categorical_question(3);
+        reveal_all(&mut question, &[(1, 2), (2, 2), (3, 1)]);
+        assert_eq!(tally_winner(&question), (0, 40.0));
+
+        let mut question = categorical_question(2);
+        reveal_all(&mut question, &[(1, 2), (OPTION_INVALID, 2)]);
+        assert_eq!(tally_winner(&question).0, 0);
+
+        assert_eq!(tally_winner(&categorical_question(2)), (0, 0.0));
+    }
+
+    #[test]
+    fn majority_rule_needs_more_than_half() {
+        let mut question = categorical_question(3);
+        question.winning_rule = WINNING_RULE_MAJORITY;
+        reveal_all(&mut question, &[(1, 2), (2, 1), (3, 1)]);
+        assert_eq!(tally_winner(&question), (0, 50.0));
+
+        reveal_all(&mut question, &[(1, 1)]);
+        assert_eq!(tally_winner(&question), (1, 60.0));
+    }
+
+    #[test]
+    fn quorum_and_required_majority_override_the_winner() {
+        let mut question = categorical_question(2);
+        question.min_revealed_voters = 3;
+        reveal_all(&mut question, &[(1, 2), (2, 1)]);
+        assert_eq!(tally_winner(&question).0, OUTCOME_NO_QUORUM);
+
+        let mut question = categorical_question(2);
+        question.required_majority_bps = 7_000;
+        reveal_all(&mut question, &[(1, 2), (2, 1)]);
+        assert_eq!(tally_winner(&question).0, OUTCOME_CONTESTED);
+
+        // A tie is not contested
+        let mut question = categorical_question(2);
+        question.required_majority_bps = 7_000;
+        reveal_all(&mut question, &[(1, 1), (2, 1)]);
+        assert_eq!(tally_winner(&question).0, 0);
+    }
+
+    #[test]
+    fn user_record_fits_its_space() {
+        let mut data = Vec::new();
+        UserRecord::default().try_serialize(&mut data).unwrap();
+        assert!(data.len() <= USER_RECORD_SPACE);
+    }
+
+    #[test]
+    fn commission_increase_applies_once_due() {
+        let operator = UserRecord {
+            stake: 100,
+            delegated_stake: 100,
+            commission_bps: 1_000,
+            pending_commission_bps: 5_000,
+            pending_commission_at: 50,
+            ..Default::default()
+        };
+        assert_eq!(commission_at(&operator, 49), 1_000);
+        assert_eq!(commission_at(&operator, 50), 5_000);
+
+        // Delegators are owed half the reward, minus the commission
+        assert_eq!(delegator_reward_share(&operator, 1_000, 49), 450);
+        assert_eq!(delegator_reward_share(&operator, 1_000, 50), 250);
+    }
+
+    #[test]
+    fn parent_outcomes_follow_the_parent_settings() {
+        let mut parent = categorical_question(3);
+        for outcome in [0, 1, 3, OPTION_INVALID] {
+            assert!(is_possible_outcome(&parent, outcome));
+        }
+        for outcome in [4, OUTCOME_VOID, OUTCOME_CONTESTED, OUTCOME_NO_QUORUM, 255] {
+            assert!(!is_possible_outcome(&parent, outcome));
+        }
+
+        parent.min_revealed_voters = 1;
+        parent.required_majority_bps = 6_000;
+        parent.parent_question = Pubkey::new_unique();
+        for outcome in [OUTCOME_VOID, OUTCOME_CONTESTED, OUTCOME_NO_QUORUM] {
+            assert!(is_possible_outcome(&parent, outcome));
+        }
+
+        let parent = scalar_question(&[], 0);
+        assert!(is_possible_outcome(&parent, 1));
+        assert!(!is_possible_outcome(&parent, 2));
+        assert!(!is_possible_outcome(&parent, OPTION_INVALID));
+    }
+
+    #[test]
+    fn required_majority_is_met_at_exactly_the_threshold() {
+        // 57 / 100 and 29 / 50 are both just below the threshold in floating point
+        for (top, total, bps) in [(57, 100, 5_700), (29, 50, 5_800)] {
+            let mut question = categorical_question(2);
+            question.required_majority_bps = bps;
+            reveal_all(&mut question, &[(1, top), (2, total - top)]);
+            assert_eq!(tally_winner(&question).0, 1);
+
+            question.required_majority_bps = bps + 1;
+            assert_eq!(tally_winner(&question).0, OUTCOME_CONTESTED);
+        }
+    }
+
+    fn max_size_question(question_text: &str) -> Question {
+        let uri = Some("u".repeat(MAX_URI_LEN));
+        Question {
+            question_text: question_text.to_string(),
+            resolution_criteria_hash: Some([0u8; 32]),
+            resolution_criteria_uri: uri.clone(),
+            source_uri: uri,
+            event_time: Some(0),
+            ..Default::default()
+        }
+    }
+
+    fn serialized_len(question: &Question) -> usize {
+        let mut data = Vec::new();
+        question.try_serialize(&mut data).unwrap();
+        data.len()
+    }
 
+    #[test]
+    fn question_space_fits_largest_scalar_question() {
+        let question_text = "q".repeat(150);
+        let mut question = max_size_question(&question_text);
+        question.question_type = QUESTION_TYPE_SCALAR;
+        question.scalar_reveals = vec![ScalarReveal { value: 0, weight: 0 }; MAX_SCALAR_REVEALS];
+
+        assert_eq!(serialized_len(&question), question_space(&question_text));
+    }
+
+    #[test]
+    fn question_space_fits_largest_categorical_question() {
+        let question_text = "q".repeat(150);
+        let mut question = max_size_question(&question_text);
+        question.options = vec!["o".repeat(MAX_OPTION_LABEL_LEN); MAX_OPTIONS];
+
+        assert!(serialized_len(&question) <= question_space(&question_text));
+    }
+
+    #[test]
+    fn reward_split_conserves_available_reward() {
+        let available_reward = 1_000_000_007;
+        for outcome in [0, 1, OPTION_INVALID, OUTCOME_NO_QUORUM, OUTCOME_CONTESTED, OUTCOME_VOID] {
+            let mut question = categorical_question(2);
+            question.winning_option = outcome;
+
+            let (fee, pool, refund) = split_reward(&question, available_reward);
+            assert_eq!(fee + pool + refund, available_reward);
+            if asker_refund_bps(&question) == 10_000 {
+                assert_eq!((fee, pool), (0, 0));
+            }
+        }
+    }
+
+    #[test]
+    fn legacy_commitment_hashes_vote_and_password_as_text() {
+        let (voter, question) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for (vote, password) in [(1u8, "hunter2"), (2, ""), (OPTION_INVALID, "12")] {
+            let expected = anchor_lang::solana_program::keccak::hash(format!("{}{}", vote, password).as_bytes()).0;
+            assert_eq!(
+                compute_commitment(COMMITMENT_VERSION_LEGACY, &voter, &question, vote, password.as_bytes()),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn bound_commitment_depends_on_voter_question_and_domain() {
+        let (voter, question) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let secret = b"hunter2";
+        let commitment = compute_commitment(COMMITMENT_VERSION_BOUND, &voter, &question, 1, secret);
+
+        let other = Pubkey::new_unique();
+        assert_ne!(compute_commitment(COMMITMENT_VERSION_BOUND, &other, &question, 1, secret), commitment);
+        assert_ne!(compute_commitment(COMMITMENT_VERSION_BOUND, &voter, &other, 1, secret), commitment);
+        assert_ne!(compute_commitment(COMMITMENT_VERSION_LEGACY, &voter, &question, 1, secret), commitment);
+
+        let (voter, question) = (voter.as_ref(), question.as_ref());
+        assert_eq!(hashv(&[COMMITMENT_DOMAIN, voter, question, &[1], secret]).0, commitment);
+        assert_ne!(hashv(&[&b"truth-net:commit:v2"[..], voter, question, &[1], secret]).0, commitment);
+        assert_ne!(hashv(&[voter, question, &[1], secret]).0, commitment);
+    }
+}
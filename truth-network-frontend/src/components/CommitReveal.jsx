@@ -8,12 +8,37 @@ import { keccak256 } from "js-sha3";
 import { getConstants } from "../constants";
 import { getIdls } from "../idl";
 import { confirmTransactionOnAllRpcs } from "../utils/confirmWithFallback";
+import { OPTION_INVALID, optionLabel } from "../utils/questionResult";
 
 const { PROGRAM_ID, getWorkingRpcUrl, getExplorerTxUrl } = getConstants();
 
+const COMMITMENT_VERSION_LEGACY = 0;
+const COMMITMENT_DOMAIN = Buffer.from("truth-net:commit:v1");
+const QUESTION_TYPE_SCALAR = 1;
+
+// Mirrors compute_commitment in the program. Questions created before
+// commitments were bound to the voter and question keep the old scheme.
+const computeCommitment = (question, voter, option, password) => {
+    if (question.commitmentVersion === COMMITMENT_VERSION_LEGACY) {
+        return Buffer.from(keccak256(option.toString() + password), "hex");
+    }
+    return Buffer.from(
+        keccak256(
+            Buffer.concat([
+                COMMITMENT_DOMAIN,
+                voter.toBuffer(),
+                new PublicKey(question.id).toBuffer(),
+                Buffer.from([option]),
+                Buffer.from(password),
+            ])
+        ),
+        "hex"
+    );
+};
+
 const CommitReveal = ({ question, onClose, refreshQuestions }) => {
     const { publicKey, signTransaction, signAllTransactions } = useWallet();
-    const [selectedOption, setSelectedOption] = useState("1");
+    const [selectedOption, setSelectedOption] = useState(1);
     const [password, setPassword] = useState("");
     const [loading, setLoading] = useState(false);
     const [hasCommitted, setHasCommitted] = useState(false);
@@ -93,7 +118,7 @@ const CommitReveal = ({ question, onClose, refreshQuestions }) => {
       
         try {
           setLoading(true);
-          const commitment = Buffer.from(keccak256(selectedOption + password), "hex");
+          const commitment = computeCommitment(question, publicKey, selectedOption, password);
           const questionPubKey = new PublicKey(question.id);
       
           const [voterRecordPDA] = PublicKey.findProgramAddressSync(
@@ -106,14 +131,29 @@ const CommitReveal = ({ question, onClose, refreshQuestions }) => {
             PROGRAM_ID
           );
       
+          const [vaultPDA] = PublicKey.findProgramAddressSync(
+            [Buffer.from("vault"), questionPubKey.toBuffer()],
+            PROGRAM_ID
+          );
+
+          const [questionContentPDA] = PublicKey.findProgramAddressSync(
+            [Buffer.from("question_content"), questionPubKey.toBuffer()],
+            PROGRAM_ID
+          );
+          const parentQuestion = new PublicKey(question.parentQuestion);
+
           // --- Build tx ---
+          // No delegated revealer: the vote is revealed from this wallet
           const tx = await program.methods
-            .commitVote(commitment)
+            .commitVote(commitment, null)
             .accounts({
               voter: publicKey,
               question: questionPubKey,
               voterRecord: voterRecordPDA,
               userRecord: userRecordPDA,
+              vault: vaultPDA,
+              questionContent: question.hasContent ? questionContentPDA : null,
+              parentQuestion: parentQuestion.equals(PublicKey.default) ? null : parentQuestion,
               systemProgram: web3.SystemProgram.programId,
             })
             .transaction();
@@ -172,6 +212,10 @@ const CommitReveal = ({ question, onClose, refreshQuestions }) => {
             "InvalidReveal": "Invalid vote commitment (cannot be empty).",
             "CommitPhaseEnded": "The commit phase has already ended for this question.",
             "AlreadyVoted": "You have already committed a vote for this question.",
+            "LeaveRequested": "You asked to leave the network and can no longer commit votes.",
+            "MembershipTooRecent": "You joined the network after this question was created.",
+            "ContentNotLocked": "The question text is still being written. Try again later.",
+            "ParentNotFinalized": "This question opens once the question it depends on is resolved.",
             "already in use": "Vote record already exists (you may have already committed).",
             "failed to find vote": "Could not find vote record. Check if the account seeds are correct.",
           };
@@ -218,6 +262,12 @@ const CommitReveal = ({ question, onClose, refreshQuestions }) => {
             PROGRAM_ID
           );
       
+          // The commit bond is returned from the question vault on reveal
+          const [vaultPDA] = PublicKey.findProgramAddressSync(
+            [Buffer.from("vault"), questionPubKey.toBuffer()],
+            PROGRAM_ID
+          );
+
           // --- Build tx ---
           const tx = await program.methods
             .revealVote(password)
@@ -226,6 +276,7 @@ const CommitReveal = ({ question, onClose, refreshQuestions }) => {
               question: questionPubKey,
               voterRecord: voterRecordPDA,
               userRecord: userRecordPDA,
+              vault: vaultPDA,
             })
             .transaction();
       
@@ -330,24 +381,34 @@ const CommitReveal = ({ question, onClose, refreshQuestions }) => {
     const now = Date.now() / 1000;
     const isCommitTimeOver = now > question.commitEndTime;
     const isRevealTime = now > question.commitEndTime && now < question.revealEndTime;
+    const voteOptions = [...question.options.map((_, index) => index + 1), OPTION_INVALID];
+
+    // Scalar votes commit a value rather than an option, which this form does not support
+    if (question.questionType === QUESTION_TYPE_SCALAR) {
+        return (
+            <div className="modal bg-white p-6 rounded-lg shadow max-w-md mx-auto">
+                <p className="text-gray-700">Numeric questions cannot be voted on from this page yet.</p>
+            </div>
+        );
+    }
 
     return (
         <div className="modal bg-white p-6 rounded-lg shadow max-w-md mx-auto">
             <p className="text-lg font-semibold mb-4">{isCommitTimeOver ? "Reveal Vote" : "Commit Vote"}</p>
 
             {!isCommitTimeOver && (
-                <div className="flex justify-center gap-4 mb-4">
-                    {["1", "2"].map((opt) => (
+                <div className="flex flex-wrap justify-center gap-4 mb-4">
+                    {voteOptions.map((opt) => (
                         <label key={opt} className="flex items-center gap-2">
                             <input
                                 type="radio"
                                 name="vote"
                                 value={opt}
                                 checked={selectedOption === opt}
-                                onChange={(e) => setSelectedOption(e.target.value)}
+                                onChange={(e) => setSelectedOption(Number(e.target.value))}
                                 disabled={hasCommitted}
                             />
-                            {opt === "1" ? "True" : "False"}
+                            {optionLabel(question.options, opt)}
                         </label>
                     ))}
                 </div>
@@ -14,11 +14,16 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
   const { publicKey, signTransaction } = useWallet();
   const [loading, setLoading] = useState(false);
   const [isMember, setIsMember] = useState(false);
+  // Unix time the user asked to leave (0 = not leaving)
+  const [leaveRequestedAt, setLeaveRequestedAt] = useState(0);
   const [connection, setConnection] = useState(null);
   const [providerCount, setProviderCount] = useState(0);
   const [statePDA] = useState(() =>
     PublicKey.findProgramAddressSync([Buffer.from("global_state")], PROGRAM_ID)[0]
   );
+  const [networkConfigPDA] = useState(() =>
+    PublicKey.findProgramAddressSync([Buffer.from("network_config")], PROGRAM_ID)[0]
+  );
 
 
   const { truthNetworkIDL } = getIdls();
@@ -76,9 +81,11 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
       const userRecordAccount = await program.account.userRecord.fetch(userRecordPDA);
       const member = !!userRecordAccount;
       setIsMember(member);
+      setLeaveRequestedAt(userRecordAccount.leaveRequestedAt.toNumber());
       updateIsMember?.(member);
     } catch (error) {
       setIsMember(false);
+      setLeaveRequestedAt(0);
       updateIsMember?.(false);
       if (error.message.includes("Account does not exist")) {
         console.log("User has not joined yet.");
@@ -121,6 +128,11 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
         [Buffer.from("invite"), publicKey.toBuffer()],
         PROGRAM_ID
       );
+
+      const [stakeVaultPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stake_vault"), publicKey.toBuffer()],
+        PROGRAM_ID
+      );
   
       let inviteExists = false;
       try {
@@ -139,8 +151,10 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
   
       const accounts = {
         globalState: statePDA,
+        networkConfig: networkConfigPDA,
         user: publicKey,
         userRecord: userRecordPDA,
+        stakeVault: stakeVaultPDA,
         systemProgram: web3.SystemProgram.programId,
         invite: inviteExists ? invitePDA : null,
       };
@@ -201,6 +215,7 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
       const errorMap = {
         "AlreadyJoined": "You already joined the network.",
         "NotInvited": "You need an invite to join right now.",
+        "AccountNotInitialized": "The network has not been configured yet.",
         "InvalidInviter": "The invite provided is invalid or doesn’t match your account.",
         "Account does not exist": "No invite was found for your account.",
       };
@@ -243,12 +258,16 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
     }
   };
   
+  // Leaving takes two steps: requestLeave starts the unbonding period, and
+  // completeLeave returns the stake once it is over
   const leaveNetworkHandler = async () => {
+    const completing = leaveRequestedAt !== 0;
+
     try {
       setLoading(true);
   
-      const [vaultPDA] = PublicKey.findProgramAddressSync(
-        [Buffer.from("vault"), publicKey.toBuffer()],
+      const [stakeVaultPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("stake_vault"), publicKey.toBuffer()],
         PROGRAM_ID
       );
   
@@ -258,15 +277,25 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
       );
   
       // --- Build tx manually ---
-      const tx = await program.methods
-        .leaveNetwork()
-        .accounts({
-          user: publicKey,
-          vault: vaultPDA,
-          userRecord: userRecordPDA,
-          systemProgram: web3.SystemProgram.programId,
-        })
-        .transaction();
+      const tx = completing
+        ? await program.methods
+            .completeLeave()
+            .accounts({
+              networkConfig: networkConfigPDA,
+              user: publicKey,
+              stakeVault: stakeVaultPDA,
+              userRecord: userRecordPDA,
+              systemProgram: web3.SystemProgram.programId,
+            })
+            .transaction()
+        : await program.methods
+            .requestLeave()
+            .accounts({
+              networkConfig: networkConfigPDA,
+              user: publicKey,
+              userRecord: userRecordPDA,
+            })
+            .transaction();
   
       tx.recentBlockhash = (await connection.getLatestBlockhash()).blockhash;
       tx.feePayer = publicKey;
@@ -279,7 +308,9 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
       if (confirmed) {
         toast.success(
           <div>
-            Successfully left the network.{" "}
+            {completing
+              ? "Successfully left the network."
+              : "Leave requested. Your stake is returned once the unbonding period is over."}{" "}
             <a
               href={getExplorerTxUrl(sig)}
               target="_blank"
@@ -315,6 +346,9 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
       // Map of known error substrings → friendly messages
       const leaveErrorMap = {
         "Account does not exist": "You don’t have a membership record to leave.",
+        "UnbondingNotOver": "The unbonding period is not over yet.",
+        "OpenCommitmentsExist": "Reveal, withdraw or reclaim your open votes before leaving.",
+        "DelegationsOutstanding": "Stake is still delegated to you. Undelegate it before leaving.",
         "signature verification failed": "Transaction signature failed (did you reject in your wallet?).",
         "insufficient funds": "Not enough SOL to pay network fees.",
       };
@@ -375,6 +409,8 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
             <span className="dot-animate dot2">.</span>
             <span className="dot-animate dot3">.</span>
           </span>
+        ) : leaveRequestedAt !== 0 ? (
+          "Complete Leaving Truth It Network"
         ) : (
           "Leave Truth It Network"
         )}
@@ -412,7 +448,9 @@ const JoinNetwork = ({ compact = false, updateIsMember }) => {
             <>
               <p className="mb-4">
                 {isMember
-                  ? "You are a Registered Truth Provider."
+                  ? leaveRequestedAt !== 0
+                    ? `You asked to leave on ${new Date(leaveRequestedAt * 1000).toLocaleString()}.`
+                    : "You are a Registered Truth Provider."
                   : "You are not registered."}
               </p>
               {renderButton()}
@@ -10,6 +10,7 @@ import CommitReveal from "./CommitReveal";
 import { getConstants } from "../constants";
 import { getIdls } from "../idl";
 import { confirmTransactionOnAllRpcs } from "../utils/confirmWithFallback";
+import { parseVotes, getLeadingOption, isRewardedOption } from "../utils/questionResult";
 
 const { PROGRAM_ID, getWorkingRpcUrl, getExplorerTxUrl, FEE_RECEIVER } = getConstants();
 
@@ -111,12 +112,14 @@ const QuestionDetail = () => {
           const revealEnded = account.revealEndTime.toNumber() <= Date.now() / 1000;
           const noOneCommitted = account.committedVoters.toNumber() === 0;
           const commitPhaseOver = account.commitEndTime.toNumber() <= now;
-          const noOneRevealed =
-            account.votesOption1.toNumber() === 0 &&
-            account.votesOption2.toNumber() === 0;
+          const noOneRevealed = account.revealedVotersCount.toNumber() === 0;
           const revealPhaseOver = account.revealEndTime.toNumber() <= now;
+          // With a quorum set, an empty question is refunded to the asker instead
+          const hasQuorum =
+            account.minRevealedVoters.toNumber() > 0 || account.minRevealedWeight.toNumber() > 0;
           const canDrainReward =
             vaultBalance > rentExemption &&
+            !hasQuorum &&
             ((commitPhaseOver && noOneCommitted) || (revealPhaseOver && noOneRevealed));
           const vaultOnlyHasRent = (vaultBalance - rentExemption) < 1000;
     
@@ -126,11 +129,12 @@ const QuestionDetail = () => {
             reward: parseFloat(solReward),
             commitEndTime: account.commitEndTime.toNumber(),
             revealEndTime: account.revealEndTime.toNumber(),
-            votesOption1: account.votesOption1.toNumber(),
-            votesOption2: account.votesOption2.toNumber(),
+            ...parseVotes(account),
             committedVoters: account.committedVoters?.toNumber?.() || 0,
-            option1: account.option1,
-            option2: account.option2,
+            questionType: account.questionType,
+            commitmentVersion: account.commitmentVersion,
+            hasContent: account.hasContent,
+            parentQuestion: account.parentQuestion.toString(),
             originalReward: account.originalReward?.toNumber?.() || 0,
             totalDistributed: account.totalDistributed?.toNumber?.() || 0,
             revealEnded,
@@ -176,27 +180,23 @@ const QuestionDetail = () => {
             );
             const voterRecordAccount = await program.account.voterRecord.fetch(voterRecordPDA);
 
+            const { totalVotes, winningOption } = getLeadingOption(questionData);
+
             const userVoterData = {
                 selectedOption: voterRecordAccount.selectedOption,
                 claimed: voterRecordAccount.claimed,
                 revealed: voterRecordAccount.revealed,
+                rewarded: isRewardedOption(winningOption, voterRecordAccount.selectedOption),
             };
 
             setUserVoterRecord(userVoterData);
 
-            const totalVotes = questionData.votesOption1 + questionData.votesOption2;
-            const winningOption = questionData.votesOption1 >= questionData.votesOption2 ? 1 : 2;
-            const winningPercentage =
-                totalVotes > 0 ? (Math.max(questionData.votesOption1, questionData.votesOption2) / totalVotes) * 100 : 0;
-
-            const isTie = questionData.votesOption1 === questionData.votesOption2;
-
             const eligibleToClaim =
                 questionData.revealEnded &&
-                userVoterData.selectedOption !== undefined &&
+                userVoterData.revealed &&
                 userVoterData.claimed === false &&
                 totalVotes > 0 &&
-                (isTie || userVoterData.selectedOption === winningOption);
+                userVoterData.rewarded;
                 
 
             setIsEligibleToClaim(eligibleToClaim);
@@ -230,8 +230,8 @@ const QuestionDetail = () => {
             PROGRAM_ID
           );
       
-          const [voterListPDA] = web3.PublicKey.findProgramAddressSync(
-            [Buffer.from("voter_list")],
+          const [userRecordPDA] = PublicKey.findProgramAddressSync(
+            [Buffer.from("user_record"), publicKey.toBuffer()],
             PROGRAM_ID
           );
       
@@ -240,6 +240,13 @@ const QuestionDetail = () => {
             PROGRAM_ID
           );
       
+          // Only operators with delegators have a pool to pay their share into
+          const [delegationPoolPDA] = PublicKey.findProgramAddressSync(
+            [Buffer.from("delegation_pool"), publicKey.toBuffer()],
+            PROGRAM_ID
+          );
+          const delegationPoolInfo = await connection.getAccountInfo(delegationPoolPDA);
+
           const txSig = web3.Keypair.generate().publicKey.toBase58();
       
           const tx = await program.methods
@@ -248,9 +255,10 @@ const QuestionDetail = () => {
               question: questionPublicKey,
               voter: publicKey,
               voterRecord: voterRecordPDA,
+              userRecord: userRecordPDA,
               vault: vaultPDA,
-              voterList: voterListPDA,
               feeReceiver: FEE_RECEIVER,
+              delegationPool: delegationPoolInfo ? delegationPoolPDA : null,
               systemProgram: web3.SystemProgram.programId,
             })
             .transaction();
@@ -370,12 +378,18 @@ const QuestionDetail = () => {
             PROGRAM_ID
           );
       
+          const [questionContentPDA] = PublicKey.findProgramAddressSync(
+            [Buffer.from("question_content"), questionPDA.toBuffer()],
+            PROGRAM_ID
+          );
+
           // --- Build transaction ---
           const tx = await program.methods
             .deleteExpiredQuestion()
             .accounts({
               question: questionPDA,
               vault: vaultPDA,
+              questionContent: question.hasContent ? questionContentPDA : null,
               asker: publicKey,
               systemProgram: web3.SystemProgram.programId,
             })
@@ -644,6 +658,17 @@ const QuestionDetail = () => {
             PROGRAM_ID
           );
       
+          const [vaultPDA] = PublicKey.findProgramAddressSync(
+            [Buffer.from("vault"), questionPDA.toBuffer()],
+            PROGRAM_ID
+          );
+
+          // Passed even after the provider has left and the record is gone
+          const [userRecordPDA] = PublicKey.findProgramAddressSync(
+            [Buffer.from("user_record"), publicKey.toBuffer()],
+            PROGRAM_ID
+          );
+
           // --- Build tx ---
           const tx = await program.methods
             .reclaimCommitOrLoserRent()
@@ -651,6 +676,8 @@ const QuestionDetail = () => {
               voter: publicKey,
               voterRecord: voterRecordPDA,
               question: questionPDA,
+              vault: vaultPDA,
+              userRecord: userRecordPDA,
             })
             .transaction();
       
@@ -833,13 +860,7 @@ const QuestionDetail = () => {
                 (!hasReclaimed || reclaiming) &&
                 (
                     !userVoterRecord.revealed ||
-                    (
-                        userVoterRecord.revealed &&
-                        question.votesOption1 !== question.votesOption2 &&
-                        userVoterRecord.selectedOption !== (
-                            question.votesOption1 > question.votesOption2 ? 1 : 2
-                        )
-                    )
+                    (userVoterRecord.revealed && !userVoterRecord.rewarded)
                 ) && (
                     <button
                         onClick={handleReclaimRent}
@@ -13,6 +13,27 @@ import { confirmTransactionOnAllRpcs } from "../utils/confirmWithFallback";
 
 const { PROGRAM_ID, getWorkingRpcUrl, getExplorerTxUrl } = getConstants();
 
+// Questions created here use the program defaults for every setting
+const defaultSettings = () => ({
+  commitBond: new BN(0),
+  minMembershipAge: null,
+  voteStake: new BN(0),
+  slashRateBps: 0,
+  voteWeightMode: 0,
+  winningRule: 0,
+  resolutionCriteriaHash: null,
+  resolutionCriteriaUri: null,
+  sourceUri: null,
+  eventTime: null,
+  minRevealedVoters: new BN(0),
+  minRevealedWeight: new BN(0),
+  requiredMajorityBps: 0,
+  parentOutcome: null,
+  extensionDelta: new BN(0),
+  maxExtensions: 0,
+  extensionThreshold: new BN(0),
+});
+
 const QuestionForm = ({ triggerRefresh, onClose }) => {
   const { publicKey, signTransaction, signAllTransactions } = useWallet();
   const [questionText, setQuestionText] = useState("");
@@ -85,6 +106,12 @@ const QuestionForm = ({ triggerRefresh, onClose }) => {
         PROGRAM_ID
       );
 
+      // Holds the default membership age for questions that do not set one
+      const [networkConfigPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from("network_config")],
+        PROGRAM_ID
+      );
+
       let questionCounterAccount = await program.account.questionCounter
         .fetch(questionCounterPDA)
         .catch(() => null);
@@ -131,12 +158,21 @@ const QuestionForm = ({ triggerRefresh, onClose }) => {
       // --- Fast path if wss://truth.it.com ---
       if (rpcUrl && rpcUrl.startsWith("wss://truth.it.com")) {
         sig = await program.methods
-          .createQuestion(questionText, rewardLamports, commitEndTimeTimestamp, revealEndTimeTimestamp)
+          .createQuestion(
+            questionText,
+            null, // default "True" / "False" labels
+            rewardLamports,
+            commitEndTimeTimestamp,
+            revealEndTimeTimestamp,
+            defaultSettings()
+          )
           .accounts({
             asker: publicKey,
             questionCounter: questionCounterPDA,
+            networkConfig: networkConfigPDA,
             question: questionPDA,
             vault: vaultPDA,
+            parentQuestion: null,
             systemProgram: web3.SystemProgram.programId,
           })
           .rpc();
@@ -145,12 +181,21 @@ const QuestionForm = ({ triggerRefresh, onClose }) => {
       } else {
         // --- Manual path ---
         const tx = await program.methods
-          .createQuestion(questionText, rewardLamports, commitEndTimeTimestamp, revealEndTimeTimestamp)
+          .createQuestion(
+            questionText,
+            null, // default "True" / "False" labels
+            rewardLamports,
+            commitEndTimeTimestamp,
+            revealEndTimeTimestamp,
+            defaultSettings()
+          )
           .accounts({
             asker: publicKey,
             questionCounter: questionCounterPDA,
+            networkConfig: networkConfigPDA,
             question: questionPDA,
             vault: vaultPDA,
+            parentQuestion: null,
             systemProgram: web3.SystemProgram.programId,
           })
           .transaction();
@@ -7,6 +7,7 @@ import { toast } from "react-toastify";
 import "react-toastify/dist/ReactToastify.css";
 import { getConstants } from "../constants";
 import { getIdls } from "../idl";
+import { parseVotes, getLeadingOption, isRewardedOption, optionLabel, formatVotes } from "../utils/questionResult";
 
 const { PROGRAM_ID, getWorkingRpcUrl } = getConstants();
 
@@ -150,8 +151,7 @@ const QuestionsList = ({ refreshKey }) => {
             commitEndTime,
             revealEndTime,
             committedVoters,
-            votesOption1: account.votesOption1.toNumber(),
-            votesOption2: account.votesOption2.toNumber(),
+            ...parseVotes(account),
             originalReward: account.originalReward?.toNumber?.() || 0,
             revealEnded,
             userVoterRecord,
@@ -282,18 +282,15 @@ const QuestionsList = ({ refreshKey }) => {
       ) : (
         <div className="grid grid-cols-1 sm:grid-cols-2 md:grid-cols-3 lg:grid-cols-4 gap-6">
           {paginatedQuestions.map((q) => {
-            const totalVotes = q.votesOption1 + q.votesOption2;
-            const winningOption = q.votesOption1 >= q.votesOption2 ? 1 : 2;
-            const winningVotes = winningOption === 1 ? q.votesOption1 : q.votesOption2;
-            const winningPercentage = totalVotes > 0 ? (winningVotes / totalVotes) * 100 : 0;
+            const { totalVotes, winningOption, winningPercentage } = getLeadingOption(q);
 
             const isEligibleToClaim =
               q.revealEnded &&
               q.userVoterRecord &&
-              q.userVoterRecord.selectedOption === winningOption &&
+              q.userVoterRecord.revealed &&
+              isRewardedOption(winningOption, q.userVoterRecord.selectedOption) &&
               !q.userVoterRecord.claimed &&
-              totalVotes > 0 &&
-              winningPercentage >= 51;
+              totalVotes > 0;
 
             const currentTime = new Date().getTime() / 1000;
             const userCanReveal =
@@ -324,9 +321,9 @@ const QuestionsList = ({ refreshKey }) => {
                 {q.revealEnded ? (
                   <>
                     <p className="text-red-600 mt-2 font-semibold">Voting Period Ended</p>
-                    <p className="text-gray-700"><strong>Winning Vote:</strong> {winningOption === 1 ? "True" : "False"}</p>
+                    <p className="text-gray-700"><strong>Winning Vote:</strong> {optionLabel(q.options, winningOption)}</p>
                     <p className="text-sm text-gray-700">
-                      <strong>Votes:</strong> {q.votesOption1} - {q.votesOption2}
+                      <strong>Votes:</strong> {formatVotes(q)}
                     </p>
                     <p className="text-gray-700"><strong>Winning Percentage:</strong> {winningPercentage.toFixed(2)}%</p>
                   </>
@@ -6,6 +6,7 @@ import { toast } from "react-toastify";
 import { getConstants } from "../constants";
 import { getIdls } from "../idl";
 import { confirmTransactionOnAllRpcs } from "../utils/confirmWithFallback";
+import { parseVotes, getLeadingOption, isRewardedOption, formatVotes } from "../utils/questionResult";
 
 const { getWorkingRpcUrl, PROGRAM_ID, getExplorerTxUrl } = getConstants();
 
@@ -607,9 +608,8 @@ const VoterDashboard = () => {
             {questions.length > 0 ? (
                 <div className="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-4">
                     {questions.map((q, index) => {
-                        const totalVotes = q.votesOption1.toNumber() + q.votesOption2.toNumber();
-                        const winningOption = q.votesOption1.toNumber() > q.votesOption2.toNumber() ? 1 : 2;
-                        const winningPercentage = (Math.max(q.votesOption1.toNumber(), q.votesOption2.toNumber()) / totalVotes) * 100;
+                        const votes = parseVotes(q);
+                        const { totalVotes, winningOption } = getLeadingOption(votes);
                         
                         
                         const currentTime = new Date().getTime() / 1000;
@@ -620,11 +620,10 @@ const VoterDashboard = () => {
                         const isEligibleToClaim =
                             revealEnd < currentTime &&
                             q.userVoterRecord &&
-                            q.userVoterRecord.selectedOption !== undefined &&
-                            q.userVoterRecord.selectedOption === winningOption &&
+                            q.userVoterRecord.revealed &&
+                            isRewardedOption(winningOption, q.userVoterRecord.selectedOption) &&
                             !q.userVoterRecord.claimed &&
-                            totalVotes > 0 &&
-                            winningPercentage >= 51;
+                            totalVotes > 0;
 
 
                         const userCanReveal =
@@ -637,14 +636,13 @@ const VoterDashboard = () => {
                         const selectedOption = q.userVoterRecord?.selectedOption;
                         const revealed = q.userVoterRecord?.revealed;
                         const claimed = q.userVoterRecord?.claimed;
-                        const isTie = q.votesOption1.toNumber() === q.votesOption2.toNumber();
                         
                         const userCanReclaimRent =
                             revealEnd < currentTime &&
                             !claimed &&
                             (
                                 revealed === false || 
-                                (!isTie && revealed === true && selectedOption !== winningOption)
+                                (revealed === true && !isRewardedOption(winningOption, selectedOption))
                             );
 
 
@@ -676,7 +674,7 @@ const VoterDashboard = () => {
                                 <p className="text-gray-700"><strong>Commit End Time:</strong> {new Date(q.commitEndTime * 1000).toLocaleString()}</p>
                                 <p className="text-gray-700"><strong>Reveal End Time:</strong> {new Date(q.revealEndTime * 1000).toLocaleString()}</p>
                                 {/* <p className="text-sm text-gray-700">
-                                    <strong>Votes:</strong> {q.votesOption1.toNumber()} - {q.votesOption2.toNumber()}
+                                    <strong>Votes:</strong> {formatVotes(votes)}
                                 </p> */}
                                 
                                 {isEligibleToClaim && (
@@ -0,0 +1,58 @@
+// Vote cast for "this question cannot be answered"
+export const OPTION_INVALID = 254;
+
+// winningOption values the program sets besides the options themselves
+const OUTCOME_LABELS = {
+  0: "Tie",
+  251: "Void",
+  252: "Contested",
+  253: "No quorum",
+  [OPTION_INVALID]: "Invalid",
+};
+const UNRESOLVED = 255;
+
+// Revealed weight per option (in label order) and for the invalid vote
+export function parseVotes(account) {
+  return {
+    options: account.options,
+    votes: account.votes.slice(0, account.options.length).map((v) => v.toNumber()),
+    invalidVotes: account.invalidVotes.toNumber(),
+    resolvedOption: account.winningOption === UNRESOLVED ? null : account.winningOption,
+  };
+}
+
+// The program's outcome once resolved, otherwise the current plurality:
+// a tie for first (or no votes at all) leads to 0
+export function getLeadingOption({ votes, invalidVotes, resolvedOption }) {
+  const totalVotes = votes.reduce((sum, v) => sum + v, 0) + invalidVotes;
+  const topVotes = Math.max(invalidVotes, ...votes);
+  const winningPercentage = totalVotes > 0 ? (topVotes / totalVotes) * 100 : 0;
+
+  let winningOption;
+  if (resolvedOption !== null && resolvedOption !== undefined) {
+    winningOption = resolvedOption;
+  } else if (totalVotes === 0 || [...votes, invalidVotes].filter((v) => v === topVotes).length > 1) {
+    winningOption = 0;
+  } else {
+    const index = votes.indexOf(topVotes);
+    winningOption = index >= 0 ? index + 1 : OPTION_INVALID;
+  }
+
+  return { totalVotes, winningOption, winningPercentage, isTie: winningOption === 0 };
+}
+
+// Whether a vote for selectedOption shares the reward under winningOption
+export function isRewardedOption(winningOption, selectedOption) {
+  if (winningOption === 0) return true;
+  if (winningOption >= 251 && winningOption <= 253) return false;
+  return selectedOption === winningOption;
+}
+
+export function optionLabel(options, option) {
+  return OUTCOME_LABELS[option] ?? options[option - 1] ?? `Option ${option}`;
+}
+
+export function formatVotes({ votes, invalidVotes }) {
+  const counts = invalidVotes > 0 ? [...votes, invalidVotes] : votes;
+  return counts.join(" - ");
+}